@@ -104,7 +104,7 @@ fn main() -> ! {
     timer.start(&mut hr_control.control);
     out1.enable();
 
-    capture.enable_interrupt(true, &mut hr_control);
+    capture.enable_interrupt(true);
     capture.add_event(&eev_input6);
 
     defmt::info!("Setup DMA");
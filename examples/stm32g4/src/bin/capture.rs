@@ -97,13 +97,13 @@ fn main() -> ! {
     out1.enable();
 
     let capture = timer.capture_ch1();
-    capture.enable_interrupt(true, &mut hr_control);
+    capture.enable_interrupt(true);
     capture.add_event(&eev_input6);
 
     let mut old_duty = 0;
     loop {
         for duty in (u32::from(period) / 10)..(9 * u32::from(period) / 10) {
-            if let Some(value) = capture.get_signed(period) {
+            if let Some(value) = capture.get_signed() {
                 defmt::info!(
                     "Capture: {:?}, duty: {}, diff: {}",
                     value,
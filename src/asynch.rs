@@ -0,0 +1,416 @@
+use atomic_waker::AtomicWaker;
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::task::Poll;
+
+use crate::capture::{CountingDirection, HrCapt, HrCapture};
+use crate::ext::MasterExt;
+#[cfg(feature = "hrtim_v2")]
+use crate::fault::FltMonitor6;
+use crate::fault::{
+    FltMonitor1, FltMonitor2, FltMonitor3, FltMonitor4, FltMonitor5, FltMonitorSys,
+};
+#[cfg(feature = "hrtim_v2")]
+use crate::pac::HRTIM_TIMF;
+use crate::pac::{HRTIM_COMMON, HRTIM_MASTER, HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC};
+use crate::pac::{HRTIM_TIMD, HRTIM_TIME};
+use crate::timer::{
+    self, ChExt, Chan, HrTim, HrTimer, HrTimerInterrupt, Instance, InstanceX, TimX, Timer,
+};
+use crate::{DacResetTrigger, HrtimPrescaler};
+
+#[cfg(feature = "hrtim_v2")]
+const TIMER_COUNT: usize = 7;
+#[cfg(any(feature = "hrtim_v1", feature = "hrtim_v1_1"))]
+const TIMER_COUNT: usize = 6;
+
+/// Per timer wakers for the repetition, capture 1 and capture 2 interrupts
+struct TimerWakers {
+    repetition: AtomicWaker,
+    capture: [AtomicWaker; 2],
+
+    /// DIER bits enabled by a future, only these are disabled again by the interrupt handler
+    armed: AtomicU32,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const TIMER_WAKERS: TimerWakers = TimerWakers {
+    repetition: AtomicWaker::new(),
+    capture: [AtomicWaker::new(), AtomicWaker::new()],
+    armed: AtomicU32::new(0),
+};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const WAKER: AtomicWaker = AtomicWaker::new();
+
+static TIMERS: [TimerWakers; TIMER_COUNT] = [TIMER_WAKERS; TIMER_COUNT];
+
+/// Fault wakers indexed by their bit in the ISR/IER registers
+static FAULTS: [AtomicWaker; 7] = [WAKER; 7];
+
+/// IER bits enabled by a future, only these are disabled again by the interrupt handler
+static FAULTS_ARMED: AtomicU32 = AtomicU32::new(0);
+
+const fn timer_index(timer: Timer) -> usize {
+    match timer {
+        Timer::Master => 0,
+        Timer::Tim(t) => t as usize + 1,
+    }
+}
+
+/// What to do in a timer interrupt, see [`timer_wakeups`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct TimerWakeups {
+    repetition: bool,
+    capture: [bool; 2],
+
+    /// DIER bits to clear
+    disable: u32,
+}
+
+/// Decide which wakers to wake and which interrupts to disable given the ISR and DIER bits of
+/// `timer`, and the DIER bits enabled by a future
+fn timer_wakeups(timer: Timer, isr: u32, dier: u32, armed: u32) -> TimerWakeups {
+    let mut handled = HrTimerInterrupt::Repetition.bits();
+    if let Timer::Tim(_) = timer {
+        handled |= HrTimerInterrupt::Capture(Chan::Ch1).bits()
+            | HrTimerInterrupt::Capture(Chan::Ch2).bits();
+    }
+
+    let pending = isr & dier & handled;
+
+    TimerWakeups {
+        repetition: pending & HrTimerInterrupt::Repetition.bits() != 0,
+        capture: [Chan::Ch1, Chan::Ch2]
+            .map(|ch| pending & HrTimerInterrupt::Capture(ch).bits() != 0),
+
+        // Disable the interrupts, the flags are left for the futures to handle. Interrupts
+        // enabled through `HrTimer::listen` are left enabled.
+        disable: pending & armed,
+    }
+}
+
+fn on_interrupt<TIM: Instance>() {
+    let tim = unsafe { &*TIM::ptr() };
+    let wakers = &TIMERS[timer_index(TIM::TIMX)];
+
+    let mut wakeups = TimerWakeups::default();
+    timer::modify_dier::<TIM>(|dier| {
+        let armed = wakers.armed.load(Ordering::Relaxed);
+        wakeups = timer_wakeups(TIM::TIMX, tim.isr().read().bits(), dier, armed);
+        wakers
+            .armed
+            .store(armed & !wakeups.disable, Ordering::Relaxed);
+        dier & !wakeups.disable
+    });
+
+    if wakeups.repetition {
+        wakers.repetition.wake();
+    }
+    for (waker, wake) in wakers.capture.iter().zip(wakeups.capture) {
+        if wake {
+            waker.wake();
+        }
+    }
+}
+
+/// Call this from the HRTIM master timer interrupt handler
+pub fn on_master_interrupt() {
+    on_interrupt::<HRTIM_MASTER>();
+}
+
+/// Call this from the HRTIM timer A-F interrupt handlers
+pub fn on_timer_interrupt(timer: TimX) {
+    match timer {
+        TimX::A => on_interrupt::<HRTIM_TIMA>(),
+        TimX::B => on_interrupt::<HRTIM_TIMB>(),
+        TimX::C => on_interrupt::<HRTIM_TIMC>(),
+        TimX::D => on_interrupt::<HRTIM_TIMD>(),
+        TimX::E => on_interrupt::<HRTIM_TIME>(),
+        #[cfg(feature = "hrtim_v2")]
+        TimX::F => on_interrupt::<HRTIM_TIMF>(),
+    }
+}
+
+/// FLT1-5 at bit 0-4, SYSFLT at bit 5 and FLT6 at bit 6 in both ISR and IER, this is also the
+/// index into [`FAULTS`]
+const FAULT_BITS: u32 = 0b111_1111;
+
+/// What to do in the fault interrupt, see [`fault_wakeups`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct FaultWakeups {
+    /// Bits of the [`FAULTS`] wakers to wake
+    wake: u32,
+
+    /// IER bits to clear
+    disable: u32,
+}
+
+/// Decide which fault wakers to wake and which interrupts to disable given the ISR and IER bits,
+/// and the IER bits enabled by a future
+fn fault_wakeups(isr: u32, ier: u32, armed: u32) -> FaultWakeups {
+    let pending = isr & ier & FAULT_BITS;
+
+    FaultWakeups {
+        wake: pending,
+
+        // Disable the interrupts, the flags are left for the futures to handle. Interrupts
+        // enabled through `FaultMonitor::enable_interrupt` are left enabled.
+        disable: pending & armed,
+    }
+}
+
+/// Call this from the HRTIM fault interrupt handler
+pub fn on_fault_interrupt() {
+    let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+    let mut wakeups = FaultWakeups::default();
+    modify_ier(|ier| {
+        let armed = FAULTS_ARMED.load(Ordering::Relaxed);
+        wakeups = fault_wakeups(common.isr().read().bits(), ier, armed);
+        FAULTS_ARMED.store(armed & !wakeups.disable, Ordering::Relaxed);
+        ier & !wakeups.disable
+    });
+
+    for (i, waker) in FAULTS.iter().enumerate() {
+        if wakeups.wake & (1 << i) != 0 {
+            waker.wake();
+        }
+    }
+}
+
+/// Read-modify-write the IER register in a critical section, it is shared between all fault
+/// monitors and the fault interrupt handler
+fn modify_ier(f: impl FnOnce(u32) -> u32) {
+    let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+    critical_section::with(|_| unsafe {
+        common.ier().modify(|r, w| w.bits(f(r.bits())));
+    });
+}
+
+/// Enable the timer interrupt `bit` for a future, unless it is already enabled
+fn arm_timer_interrupt<TIM: Instance>(bit: u32) {
+    let armed = &TIMERS[timer_index(TIM::TIMX)].armed;
+
+    timer::modify_dier::<TIM>(|dier| {
+        if dier & bit == 0 {
+            armed.store(armed.load(Ordering::Relaxed) | bit, Ordering::Relaxed);
+        }
+        dier | bit
+    });
+}
+
+/// Enable the fault interrupt `bit` for a future, unless it is already enabled
+fn arm_fault_interrupt(bit: u32) {
+    modify_ier(|ier| {
+        if ier & bit == 0 {
+            FAULTS_ARMED.store(
+                FAULTS_ARMED.load(Ordering::Relaxed) | bit,
+                Ordering::Relaxed,
+            );
+        }
+        ier | bit
+    });
+}
+
+impl<TIM: Instance, PSCL: HrtimPrescaler, CPT1, CPT2, DacRst: DacResetTrigger>
+    HrTim<TIM, PSCL, CPT1, CPT2, DacRst>
+{
+    /// Wait for the next repetition event
+    ///
+    /// NOTE: Requires [`on_master_interrupt`] or [`on_timer_interrupt`] to be called from the timer's interrupt handler
+    pub async fn wait_for_repetition(&mut self) {
+        self.clear(HrTimerInterrupt::Repetition);
+
+        poll_fn(|cx| {
+            TIMERS[timer_index(TIM::TIMX)]
+                .repetition
+                .register(cx.waker());
+
+            if self.is_pending(HrTimerInterrupt::Repetition) {
+                self.clear(HrTimerInterrupt::Repetition);
+                Poll::Ready(())
+            } else {
+                arm_timer_interrupt::<TIM>(HrTimerInterrupt::Repetition.bits());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<TIM: InstanceX, PSCL, CH: ChExt, DMA> HrCapt<TIM, PSCL, CH, DMA> {
+    /// Wait for the next capture
+    ///
+    /// NOTE: Requires [`on_timer_interrupt`] to be called from the timer's interrupt handler
+    pub async fn wait(&mut self) -> (u16, CountingDirection) {
+        poll_fn(|cx| {
+            TIMERS[timer_index(TIM::TIMX)].capture[CH::CH as usize].register(cx.waker());
+
+            if let Some(value) = self.get() {
+                Poll::Ready(value)
+            } else {
+                arm_timer_interrupt::<TIM>(HrTimerInterrupt::Capture(CH::CH).bits());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+macro_rules! impl_flt_monitor_async {
+    ($($t:ident: ($fltx:ident, $i:literal),)+) => {$(
+        impl $t {
+            /// Wait for a fault to be active
+            ///
+            /// Returns immediately if a fault is already active.
+            ///
+            /// NOTE: Requires [`on_fault_interrupt`] to be called from the fault interrupt handler
+            pub async fn wait_for_fault(&mut self) {
+                poll_fn(|cx| {
+                    FAULTS[$i].register(cx.waker());
+
+                    let common = unsafe { &*HRTIM_COMMON::ptr() };
+                    if common.isr().read().$fltx().bit() {
+                        Poll::Ready(())
+                    } else {
+                        arm_fault_interrupt(1 << $i);
+                        Poll::Pending
+                    }
+                })
+                .await
+            }
+        }
+    )+};
+}
+
+impl_flt_monitor_async!(
+    FltMonitor1: (flt1, 0),
+    FltMonitor2: (flt2, 1),
+    FltMonitor3: (flt3, 2),
+    FltMonitor4: (flt4, 3),
+    FltMonitor5: (flt5, 4),
+    FltMonitorSys: (sysflt, 5),
+);
+
+#[cfg(feature = "hrtim_v2")]
+impl_flt_monitor_async!(
+    FltMonitor6: (flt6, 6),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REP: u32 = HrTimerInterrupt::Repetition.bits();
+    const CPT1: u32 = HrTimerInterrupt::Capture(Chan::Ch1).bits();
+    const CPT2: u32 = HrTimerInterrupt::Capture(Chan::Ch2).bits();
+    const CMP1: u32 = HrTimerInterrupt::Compare(crate::timer::Cmp::Cmp1).bits();
+    const ALL: u32 = u32::MAX;
+
+    #[test]
+    fn timer_index_is_unique() {
+        let mut seen = [false; TIMER_COUNT];
+        let timers = [
+            TimX::A,
+            TimX::B,
+            TimX::C,
+            TimX::D,
+            TimX::E,
+            #[cfg(feature = "hrtim_v2")]
+            TimX::F,
+        ];
+        for timer in [Timer::Master].into_iter().chain(timers.map(Timer::Tim)) {
+            let i = timer_index(timer);
+            assert!(!seen[i]);
+            seen[i] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+
+    #[test]
+    fn only_enabled_and_pending_are_woken() {
+        let timer = Timer::Tim(TimX::A);
+
+        // Pending but not enabled
+        assert_eq!(
+            timer_wakeups(timer, REP | CPT1, 0, ALL),
+            TimerWakeups::default()
+        );
+
+        // Enabled but not pending
+        assert_eq!(
+            timer_wakeups(timer, 0, REP | CPT1, ALL),
+            TimerWakeups::default()
+        );
+
+        let wakeups = timer_wakeups(timer, REP | CPT2, REP | CPT1 | CPT2, ALL);
+        assert_eq!(
+            wakeups,
+            TimerWakeups {
+                repetition: true,
+                capture: [false, true],
+                disable: REP | CPT2,
+            }
+        );
+    }
+
+    #[test]
+    fn other_interrupts_are_left_alone() {
+        let wakeups = timer_wakeups(Timer::Tim(TimX::B), CMP1 | CPT1, CMP1 | CPT1, ALL);
+        assert_eq!(wakeups.capture, [true, false]);
+        assert_eq!(wakeups.disable, CPT1);
+    }
+
+    #[test]
+    fn master_has_no_captures() {
+        // Bit 7 and 8 are not capture interrupts on the master timer
+        let wakeups = timer_wakeups(Timer::Master, REP | CPT1 | CPT2, REP | CPT1 | CPT2, ALL);
+        assert_eq!(
+            wakeups,
+            TimerWakeups {
+                repetition: true,
+                capture: [false, false],
+                disable: REP,
+            }
+        );
+    }
+
+    #[test]
+    fn fault_wakeups_ignore_other_bits() {
+        let burst_and_register_update = 0b11 << 16;
+        let isr = 0b010_0101 | burst_and_register_update;
+        let ier = 0b110_0001 | burst_and_register_update;
+        assert_eq!(
+            fault_wakeups(isr, ier, u32::MAX),
+            FaultWakeups {
+                wake: 0b010_0001,
+                disable: 0b010_0001,
+            }
+        );
+    }
+
+    #[test]
+    fn listened_interrupts_stay_enabled() {
+        // Repetition enabled through `HrTimer::listen`, capture 1 by a future
+        let wakeups = timer_wakeups(Timer::Tim(TimX::C), REP | CPT1, REP | CPT1, CPT1);
+        assert_eq!(
+            wakeups,
+            TimerWakeups {
+                repetition: true,
+                capture: [true, false],
+                disable: CPT1,
+            }
+        );
+
+        // Fault 1 enabled through `FaultMonitor::enable_interrupt`, fault 2 by a future
+        assert_eq!(
+            fault_wakeups(0b11, 0b11, 0b10),
+            FaultWakeups {
+                wake: 0b11,
+                disable: 0b10,
+            }
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::control::HrPwmCtrl;
+use crate::dma::{DmaPeripheral, DmaWritable};
+use crate::pac::HRTIM_COMMON;
+use crate::timer::TimX;
+
+#[cfg(feature = "hrtim_v2")]
+const TIMER_COUNT: usize = 6;
+#[cfg(any(feature = "hrtim_v1", feature = "hrtim_v1_1"))]
+const TIMER_COUNT: usize = 5;
+
+/// Master timer registers which may be updated by the burst DMA
+///
+/// The discriminant is the bit in BDMUPR
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterBurstRegister {
+    Cr = 0,
+    Icr = 1,
+    Dier = 2,
+    Cnt = 3,
+    Per = 4,
+    Rep = 5,
+    Cmp1 = 6,
+    Cmp2 = 7,
+    Cmp3 = 8,
+    Cmp4 = 9,
+}
+
+/// Timer A..F registers which may be updated by the burst DMA
+///
+/// The discriminant is the bit in BDTxUPR
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BurstRegister {
+    Cr = 0,
+    Icr = 1,
+    Dier = 2,
+    Cnt = 3,
+    Per = 4,
+    Rep = 5,
+    Cmp1 = 6,
+    Cmp2 = 7,
+    Cmp3 = 8,
+    Cmp4 = 9,
+    Dtr = 10,
+    Set1r = 11,
+    Rst1r = 12,
+    Set2r = 13,
+    Rst2r = 14,
+    Eefr1 = 15,
+    Eefr2 = 16,
+    Rstr = 17,
+    Chpr = 18,
+    Outr = 19,
+    Fltr = 20,
+    #[cfg(feature = "hrtim_v2")]
+    Cr2 = 21,
+    #[cfg(feature = "hrtim_v2")]
+    Eefr3 = 22,
+}
+
+/// Builder for the burst DMA which allows for updating any set of timer registers
+/// from a single DMA transfer
+///
+/// ```
+/// let burst_dma = BurstDmaBuilder::default()
+///     .timer(TimX::A, &[BurstRegister::Per, BurstRegister::Cmp1, BurstRegister::Dtr])
+///     .timer(TimX::B, &[BurstRegister::Per, BurstRegister::Cmp1, BurstRegister::Dtr])
+///     .finalize(&mut hr_control.control);
+///
+/// let mut buffer = [0; 6];
+/// burst_dma.set(&mut buffer, TimX::A, BurstRegister::Per, 20_000);
+/// ...
+/// // Transfer `buffer` to `burst_dma.address()` using the DMA, triggered by for example
+/// // the master timer's repetition DMA request
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BurstDmaBuilder {
+    master_bits: u32,
+    timer_bits: [u32; TIMER_COUNT],
+}
+
+impl BurstDmaBuilder {
+    /// Update the specified master timer registers from the burst
+    pub fn master(mut self, registers: &[MasterBurstRegister]) -> Self {
+        for r in registers {
+            self.master_bits |= 1 << *r as u32;
+        }
+        self
+    }
+
+    /// Update the specified registers of `timer` from the burst
+    pub fn timer(mut self, timer: TimX, registers: &[BurstRegister]) -> Self {
+        for r in registers {
+            self.timer_bits[timer as usize] |= 1 << *r as u32;
+        }
+        self
+    }
+
+    pub fn finalize(self, _control: &mut HrPwmCtrl) -> BurstDma {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        let bits = self.timer_bits;
+
+        // SAFETY: We hold _control, BurstRegister and MasterBurstRegister only contain valid bits
+        unsafe {
+            common.bdmupr().write(|w| w.bits(self.master_bits));
+            common.bdtaupr().write(|w| w.bits(bits[0]));
+            common.bdtbupr().write(|w| w.bits(bits[1]));
+            common.bdtcupr().write(|w| w.bits(bits[2]));
+            common.bdtdupr().write(|w| w.bits(bits[3]));
+            common.bdteupr().write(|w| w.bits(bits[4]));
+            #[cfg(feature = "hrtim_v2")]
+            common.bdtfupr().write(|w| w.bits(bits[5]));
+        }
+
+        BurstDma {
+            master_bits: self.master_bits,
+            timer_bits: self.timer_bits,
+        }
+    }
+}
+
+/// Configured burst DMA, see [`BurstDmaBuilder`]
+///
+/// Each burst writes `len()` words to BDMADR. The words are written to the
+/// selected master timer registers first, then to the selected registers of
+/// timer A, timer B and so on. Within each timer, the registers are written
+/// in the order of their addresses.
+#[derive(Debug, Clone, Copy)]
+pub struct BurstDma {
+    master_bits: u32,
+    timer_bits: [u32; TIMER_COUNT],
+}
+
+impl BurstDma {
+    /// Number of words in each burst
+    pub fn len(&self) -> usize {
+        self.timer_bits
+            .iter()
+            .fold(self.master_bits.count_ones(), |n, bits| {
+                n + bits.count_ones()
+            }) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index of the master timer register in the burst buffer
+    ///
+    /// Returns `None` if the register is not part of the burst
+    pub fn master_index_of(&self, register: MasterBurstRegister) -> Option<usize> {
+        index_of(self.master_bits, register as u32, 0)
+    }
+
+    /// Index of the timer register in the burst buffer
+    ///
+    /// Returns `None` if the register is not part of the burst
+    pub fn index_of(&self, timer: TimX, register: BurstRegister) -> Option<usize> {
+        let offset = self.timer_bits[..timer as usize]
+            .iter()
+            .fold(self.master_bits.count_ones(), |n, bits| {
+                n + bits.count_ones()
+            });
+
+        index_of(self.timer_bits[timer as usize], register as u32, offset)
+    }
+
+    /// Write `value` to the position of the master timer register in `buffer`
+    ///
+    /// Panic if the register is not part of the burst or if `buffer` is too short
+    pub fn set_master(&self, buffer: &mut [u32], register: MasterBurstRegister, value: u32) {
+        let i = self
+            .master_index_of(register)
+            .expect("Register not in burst");
+        buffer[i] = value;
+    }
+
+    /// Write `value` to the position of the timer register in `buffer`
+    ///
+    /// Panic if the register is not part of the burst or if `buffer` is too short
+    pub fn set(&self, buffer: &mut [u32], timer: TimX, register: BurstRegister, value: u32) {
+        let i = self
+            .index_of(timer, register)
+            .expect("Register not in burst");
+        buffer[i] = value;
+    }
+}
+
+fn index_of(bits: u32, bit: u32, offset: u32) -> Option<usize> {
+    if bits & (1 << bit) == 0 {
+        return None;
+    }
+
+    // Count the number of registers before this one
+    let below = bits & ((1 << bit) - 1);
+    Some((offset + below.count_ones()) as usize)
+}
+
+/// The burst DMA data register, BDMADR
+unsafe impl DmaPeripheral for BurstDma {
+    type Word = u32;
+
+    fn address(&self) -> u32 {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        common.bdmadr().as_ptr() as u32
+    }
+}
+
+unsafe impl DmaWritable for BurstDma {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Same as `BurstDmaBuilder::finalize` without touching the registers
+    fn burst(builder: BurstDmaBuilder) -> BurstDma {
+        BurstDma {
+            master_bits: builder.master_bits,
+            timer_bits: builder.timer_bits,
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let burst = burst(BurstDmaBuilder::default());
+        assert!(burst.is_empty());
+        assert_eq!(burst.master_index_of(MasterBurstRegister::Per), None);
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Per), None);
+    }
+
+    #[test]
+    fn master_first() {
+        let burst = burst(
+            BurstDmaBuilder::default()
+                .timer(TimX::A, &[BurstRegister::Cmp1])
+                .master(&[MasterBurstRegister::Per, MasterBurstRegister::Cmp1]),
+        );
+
+        assert_eq!(burst.len(), 3);
+        assert_eq!(burst.master_index_of(MasterBurstRegister::Per), Some(0));
+        assert_eq!(burst.master_index_of(MasterBurstRegister::Cmp1), Some(1));
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Cmp1), Some(2));
+    }
+
+    #[test]
+    fn skipped_timers() {
+        let burst = burst(
+            BurstDmaBuilder::default()
+                .timer(TimX::E, &[BurstRegister::Per])
+                .timer(TimX::B, &[BurstRegister::Per, BurstRegister::Cmp1]),
+        );
+
+        assert_eq!(burst.len(), 3);
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Per), None);
+        assert_eq!(burst.index_of(TimX::B, BurstRegister::Per), Some(0));
+        assert_eq!(burst.index_of(TimX::B, BurstRegister::Cmp1), Some(1));
+        assert_eq!(burst.index_of(TimX::C, BurstRegister::Per), None);
+        assert_eq!(burst.index_of(TimX::E, BurstRegister::Per), Some(2));
+    }
+
+    #[test]
+    fn register_order_within_timer() {
+        // Registers are in address order, regardless of the order they are given in
+        let burst = burst(BurstDmaBuilder::default().timer(
+            TimX::A,
+            &[
+                BurstRegister::Fltr,
+                BurstRegister::Dtr,
+                BurstRegister::Per,
+                BurstRegister::Cmp3,
+            ],
+        ));
+
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Per), Some(0));
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Cmp3), Some(1));
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Dtr), Some(2));
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Fltr), Some(3));
+        assert_eq!(burst.index_of(TimX::A, BurstRegister::Cmp1), None);
+    }
+
+    #[test]
+    fn set_writes_to_index() {
+        let burst = burst(
+            BurstDmaBuilder::default()
+                .master(&[MasterBurstRegister::Per])
+                .timer(TimX::A, &[BurstRegister::Per, BurstRegister::Dtr])
+                .timer(TimX::B, &[BurstRegister::Cmp1]),
+        );
+
+        let mut buffer = [0; 4];
+        burst.set_master(&mut buffer, MasterBurstRegister::Per, 1);
+        burst.set(&mut buffer, TimX::A, BurstRegister::Per, 2);
+        burst.set(&mut buffer, TimX::A, BurstRegister::Dtr, 3);
+        burst.set(&mut buffer, TimX::B, BurstRegister::Cmp1, 4);
+        assert_eq!(buffer, [1, 2, 3, 4]);
+    }
+}
@@ -1,7 +1,9 @@
-use super::timer::{self, InstanceX};
+use super::timer::{self, HrTimerInterrupt, InstanceX};
 pub use super::timer::{Ch1, Ch2, ChExt};
 use crate::ext::{CptcrW, MasterExt, TimExt};
+use core::iter::Chain;
 use core::marker::PhantomData;
+use core::slice;
 
 pub struct Dma;
 pub struct NoDma;
@@ -87,11 +89,13 @@ pub trait HrCapture {
     /// Negative result | positive result
     /// ```
     ///
+    /// The period is read from the timer, see [`Self::get_last_signed`]
+    ///
     /// NOTE: This function will use [`Self::is_pending`] to check if there is a value available and
     /// [`Self::clear_interrupt`] to clear it.
-    fn get_signed(&mut self, period: u16) -> Option<i32> {
+    fn get_signed(&mut self) -> Option<i32> {
         if self.is_pending() {
-            let value = self.get_last_signed(period);
+            let value = self.get_last_signed();
             self.clear_interrupt();
             Some(value)
         } else {
@@ -117,17 +121,23 @@ pub trait HrCapture {
     /// <-------------- 0 --------------> t
     /// Negative result | positive result
     /// ```
-    fn get_last_signed(&self, #[allow(unused_variables)] period: u16) -> i32 {
+    ///
+    /// The period is read from the timer. Captures are only ever marked as
+    /// [`CountingDirection::Down`] in [`crate::HrCountingDirection::UpDown`] mode.
+    fn get_last_signed(&self) -> i32 {
         let (value, dir) = self.get_last();
 
         // The capture counter always counts up and restarts at period
         match dir {
             CountingDirection::Up => i32::from(value),
             #[cfg(feature = "hrtim_v2")]
-            CountingDirection::Down => i32::from(value) - i32::from(period),
+            CountingDirection::Down => i32::from(value) - i32::from(self.get_period()),
         }
     }
 
+    /// Period of the timer this capture channel belongs to
+    fn get_period(&self) -> u16;
+
     fn clear_interrupt(&mut self);
 
     fn is_pending(&self) -> bool;
@@ -156,6 +166,120 @@ pub fn dma_value_to_signed(x: u32, #[allow(unused_variables)] period: u16) -> i3
     }
 }
 
+/// Zero-copy iterator over the values in a DMA capture buffer, see [`dma_value_to_dir_and_value`]
+///
+/// Handles circular buffers where the oldest value is not at the start of the buffer
+///
+/// ```
+/// // Everything written since last time, `pos` is the DMA's current write position
+/// for value in CaptureValues::between(&buffer, last_pos, pos).signed(period) {
+///     ...
+/// }
+/// last_pos = pos;
+/// ```
+#[derive(Clone, Debug)]
+pub struct CaptureValues<'a> {
+    values: Chain<slice::Iter<'a, u32>, slice::Iter<'a, u32>>,
+}
+
+impl<'a> CaptureValues<'a> {
+    /// All values from the start to the end of `buffer`
+    pub fn new(buffer: &'a [u32]) -> Self {
+        Self::from_parts(buffer, &buffer[..0])
+    }
+
+    /// All values of a circular `buffer`, oldest first, where `write_pos` is the index the
+    /// next value will be written to
+    ///
+    /// Panic if `write_pos` is outside of the buffer
+    pub fn circular(buffer: &'a [u32], write_pos: usize) -> Self {
+        let (newest, oldest) = buffer.split_at(write_pos);
+        Self::from_parts(oldest, newest)
+    }
+
+    /// The values of a circular `buffer` from `read_pos` up to, but not including, `write_pos`
+    ///
+    /// Wraps around at the end of the buffer. Nothing is returned if both are the same.
+    ///
+    /// Panic if `read_pos` or `write_pos` is outside of the buffer
+    pub fn between(buffer: &'a [u32], read_pos: usize, write_pos: usize) -> Self {
+        assert!(read_pos <= buffer.len() && write_pos <= buffer.len());
+
+        if read_pos <= write_pos {
+            Self::from_parts(&buffer[read_pos..write_pos], &buffer[..0])
+        } else {
+            Self::from_parts(&buffer[read_pos..], &buffer[..write_pos])
+        }
+    }
+
+    /// Map the values to ticks relative to the beginning of up counting, see [`dma_value_to_signed`]
+    pub fn signed(self, period: u16) -> SignedCaptureValues<'a> {
+        SignedCaptureValues {
+            values: self.values,
+            period,
+        }
+    }
+
+    fn from_parts(first: &'a [u32], second: &'a [u32]) -> Self {
+        Self {
+            values: first.iter().chain(second.iter()),
+        }
+    }
+}
+
+impl Iterator for CaptureValues<'_> {
+    type Item = (u16, CountingDirection);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.next().map(|&x| dma_value_to_dir_and_value(x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for CaptureValues<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.values
+            .next_back()
+            .map(|&x| dma_value_to_dir_and_value(x))
+    }
+}
+
+impl ExactSizeIterator for CaptureValues<'_> {}
+
+/// Signed values of a DMA capture buffer, see [`CaptureValues::signed`]
+#[derive(Clone, Debug)]
+pub struct SignedCaptureValues<'a> {
+    values: Chain<slice::Iter<'a, u32>, slice::Iter<'a, u32>>,
+    period: u16,
+}
+
+impl Iterator for SignedCaptureValues<'_> {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values
+            .next()
+            .map(|&x| dma_value_to_signed(x, self.period))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for SignedCaptureValues<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.values
+            .next_back()
+            .map(|&x| dma_value_to_signed(x, self.period))
+    }
+}
+
+impl ExactSizeIterator for SignedCaptureValues<'_> {}
+
 impl<TIM: InstanceX, CH: ChExt, PSCL> HrCapt<TIM, PSCL, CH, NoDma> {
     /// Add event to capture
     ///
@@ -188,23 +312,37 @@ impl<TIM: InstanceX, CH: ChExt, PSCL> HrCapt<TIM, PSCL, CH, NoDma> {
         tim.cptcr(CH::CH).modify(|_, w| w.set_swcpt());
     }
 
-    // TODO: It would be sufficient to instead of hr_control only require exclusive access to the owning timer
-    // however that would be hard to do since typically the capture device is a field of that same timer.
-    // Would it make more sense to have this method directly on HrTim instead?
-    pub fn enable_interrupt(&mut self, enable: bool, _hr_control: &mut super::HrPwmControl) {
-        let tim = unsafe { &*TIM::ptr() };
+    /// Enable or disable the capture interrupt
+    ///
+    /// See also [`crate::timer::HrTimer::listen`] with [`crate::timer::HrTimerInterrupt::Capture`]
+    pub fn enable_interrupt(&mut self, enable: bool) {
+        let bit = HrTimerInterrupt::Capture(CH::CH).bits();
 
-        tim.dier().modify(|_r, w| w.cptie(CH::CH as _).bit(enable));
+        // The timer, and thus DIER, is shared with other capture channels and `HrTimer::listen`
+        timer::modify_dier::<TIM>(|bits| if enable { bits | bit } else { bits & !bit });
     }
 
     pub fn enable_dma(self, _ch: timer::DmaChannel<TIM>) -> HrCapt<TIM, PSCL, CH, Dma> {
-        // SAFETY: We own the only instance of this timers dma channel, no one else can do this
-        let tim = unsafe { &*TIM::ptr() };
-        tim.dier().modify(|_r, w| w.cptde(CH::CH as _).set_bit());
+        // The DMA enable bits are 16 bits above the interrupt enable bits
+        let bit = HrTimerInterrupt::Capture(CH::CH).bits() << 16;
+        timer::modify_dier::<TIM>(|bits| bits | bit);
         HrCapt { _x: PhantomData }
     }
 }
 
+impl<TIM: InstanceX, CH: ChExt, PSCL> HrCapt<TIM, PSCL, CH, Dma> {
+    /// Stop the DMA requests and give back the timer's dma channel
+    pub fn disable_dma(self) -> (HrCapt<TIM, PSCL, CH, NoDma>, timer::DmaChannel<TIM>) {
+        let bit = HrTimerInterrupt::Capture(CH::CH).bits() << 16;
+        timer::modify_dier::<TIM>(|bits| bits & !bit);
+
+        // SAFETY: We consumed the only instance of this timers dma channel in `enable_dma`
+        (HrCapt { _x: PhantomData }, unsafe {
+            timer::DmaChannel::new()
+        })
+    }
+}
+
 impl<TIM: InstanceX, CH: ChExt, PSCL, DMA> HrCapture for HrCapt<TIM, PSCL, CH, DMA> {
     fn get_last(&self) -> (u16, CountingDirection) {
         let tim = unsafe { &*TIM::ptr() };
@@ -223,6 +361,13 @@ impl<TIM: InstanceX, CH: ChExt, PSCL, DMA> HrCapture for HrCapt<TIM, PSCL, CH, D
         (value, dir)
     }
 
+    fn get_period(&self) -> u16 {
+        let tim = unsafe { &*TIM::ptr() };
+
+        // No need for exclusive access since this is only a read
+        tim.perr().read().per().bits()
+    }
+
     fn clear_interrupt(&mut self) {
         let tim = unsafe { &*TIM::ptr() };
 
@@ -237,3 +382,190 @@ impl<TIM: InstanceX, CH: ChExt, PSCL, DMA> HrCapture for HrCapt<TIM, PSCL, CH, D
         tim.isr().read().cpt(CH::CH as _).bit()
     }
 }
+
+/// Capture with extended, 64 bit, timestamps
+///
+/// The 16 bit capture value wraps every period. This keeps count of the timer roll-overs
+/// using the repetition interrupt and combines it with the capture value into a monotonic
+/// timestamp in timer ticks.
+///
+/// The timer has to run in [`crate::HrCountingDirection::Up`] mode with a repetition counter
+/// of 0, so that the repetition interrupt fires on every roll-over.
+///
+/// ```
+/// let TimerSplitCapture { mut timer, ch1, .. } = timer.split_capture();
+/// let mut capture = ExtendedCapture::new(ch1, &mut timer);
+/// timer.listen(HrTimerInterrupt::Repetition);
+/// timer.listen(HrTimerInterrupt::Capture(Chan::Ch1));
+///
+/// // In the timer's interrupt handler
+/// if let Some(timestamp) = capture.get() {
+///     ...
+/// }
+/// capture.on_rollover();
+/// ```
+///
+/// NOTE: Both [`Self::get`] and [`Self::on_rollover`] have to be called within half a period of
+/// the events for a capture close to a roll-over to be attributed to the correct period.
+///
+/// NOTE: Both have to be called from the same interrupt handler, with [`Self::get`] first. A
+/// roll-over is left pending while a capture is unread, so the repetition interrupt keeps
+/// firing until the capture has been read.
+pub struct ExtendedCapture<TIM, PSCL, CH> {
+    capture: HrCapt<TIM, PSCL, CH, NoDma>,
+    rollovers: u64,
+}
+
+impl<TIM: InstanceX, PSCL, CH: ChExt> ExtendedCapture<TIM, PSCL, CH> {
+    /// Panic if the repetition counter of the timer is not 0
+    pub fn new<T>(capture: HrCapt<TIM, PSCL, CH, NoDma>, timer: &mut T) -> Self
+    where
+        T: timer::HrTimer<Timer = TIM>,
+    {
+        let tim = unsafe { &*TIM::ptr() };
+        assert_eq!(tim.repr().read().rep().bits(), 0);
+
+        timer.clear_repetition_interrupt();
+
+        Self {
+            capture,
+            rollovers: 0,
+        }
+    }
+
+    /// Count a pending roll-over, call this from the repetition interrupt
+    ///
+    /// A roll-over is left pending while there is an unread capture, since the capture
+    /// may have happened just before it. It is then counted by [`Self::get`] instead.
+    ///
+    /// NOTE: The repetition flag is not cleared in that case, so the interrupt is re-entered
+    /// immediately unless [`Self::get`] is called from the same handler before returning.
+    ///
+    /// Returns true if a roll-over was counted
+    pub fn on_rollover(&mut self) -> bool {
+        if self.capture.is_pending() || !is_rollover_pending::<TIM>() {
+            return false;
+        }
+
+        self.count_rollover();
+        true
+    }
+
+    /// Try to get the timestamp of the last capture in timer ticks
+    ///
+    /// Returns none if no edge has been captured since last time
+    pub fn get(&mut self) -> Option<u64> {
+        if !self.capture.is_pending() {
+            return None;
+        }
+
+        let (value, _dir) = self.capture.get_last();
+        self.capture.clear_interrupt();
+        let period = self.capture.get_period();
+
+        // A roll-over which is not counted yet happened either just before or just after
+        // the capture, a small capture value means the capture happened after it
+        let mut rollovers = self.rollovers;
+        if is_rollover_pending::<TIM>() {
+            if value < period / 2 {
+                rollovers += 1;
+            }
+            self.count_rollover();
+        }
+
+        Some(rollovers * u64::from(period) + u64::from(value))
+    }
+
+    /// Same as [`Self::get`] but truncated to 32 bits, wraps around on overflow
+    pub fn get_u32(&mut self) -> Option<u32> {
+        self.get().map(|timestamp| timestamp as u32)
+    }
+
+    /// Number of roll-overs counted so far
+    pub fn rollovers(&self) -> u64 {
+        self.rollovers
+    }
+
+    pub fn free(self) -> HrCapt<TIM, PSCL, CH, NoDma> {
+        self.capture
+    }
+
+    fn count_rollover(&mut self) {
+        let tim = unsafe { &*TIM::ptr() };
+
+        // No need for exclusive access since this is a write only register
+        tim.icr().write(|w| w.repc().clear());
+        self.rollovers += 1;
+    }
+}
+
+fn is_rollover_pending<TIM: InstanceX>() -> bool {
+    let tim = unsafe { &*TIM::ptr() };
+
+    // No need for exclusive access since this is a read only register
+    tim.isr().read().rep().bit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUFFER: [u32; 5] = [0, 1, 2, 3, 4];
+
+    fn values(values: CaptureValues<'_>) -> impl Iterator<Item = u16> + '_ {
+        values.map(|(value, dir)| {
+            assert!(matches!(dir, CountingDirection::Up));
+            value
+        })
+    }
+
+    #[test]
+    fn circular_oldest_first() {
+        let v = CaptureValues::circular(&BUFFER, 2);
+        assert_eq!(v.len(), 5);
+        assert!(values(v).eq([2, 3, 4, 0, 1]));
+
+        assert!(values(CaptureValues::circular(&BUFFER, 0)).eq([0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn circular_write_pos_at_end() {
+        assert!(values(CaptureValues::circular(&BUFFER, 5)).eq([0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn between_without_wrap() {
+        assert!(values(CaptureValues::between(&BUFFER, 1, 3)).eq([1, 2]));
+    }
+
+    #[test]
+    fn between_wraps_when_read_pos_is_after_write_pos() {
+        let v = CaptureValues::between(&BUFFER, 3, 1);
+        assert_eq!(v.len(), 3);
+        assert!(values(v).eq([3, 4, 0]));
+
+        let v = CaptureValues::between(&BUFFER, 3, 1).rev();
+        assert!(v.map(|(value, _)| value).eq([0, 4, 3]));
+    }
+
+    #[test]
+    fn between_same_pos_is_empty() {
+        for pos in 0..=BUFFER.len() {
+            assert_eq!(CaptureValues::between(&BUFFER, pos, pos).len(), 0);
+        }
+    }
+
+    #[test]
+    fn between_write_pos_at_end() {
+        assert!(values(CaptureValues::between(&BUFFER, 3, 5)).eq([3, 4]));
+
+        // Reading at the end of the buffer wraps to the start
+        assert!(values(CaptureValues::between(&BUFFER, 5, 2)).eq([0, 1]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn between_outside_of_buffer() {
+        CaptureValues::between(&BUFFER, 0, 6);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::compare_register::{HrCompareRegister, HrCr1, HrCr2, SymmetricDuty};
+use crate::control::HrPwmCtrl;
+use crate::ext::{MasterCrR, MasterExt};
+use crate::output::HrOutput;
+use crate::timer::{HrTimer, InstanceX};
+use crate::{HrCountingDirection, HrtimPrescaler};
+
+/// Center aligned PWM emulated in up counting mode
+///
+/// The output is set on cr1 and reset on cr2, placed symmetrically around the
+/// middle of the period. This works the same on all devices, including HRTIM v1
+/// devices which lack [`HrCountingDirection::UpDown`].
+///
+/// See [`SymmetricDuty`] for using the same control code as with `UpDown` mode, where
+/// the on time is also centered on the middle of the effective period.
+///
+/// ```text
+///            |<-duty->|
+///            *--------*
+///            |        |
+/// -----------*        *-----------
+/// 0         cr1  ^   cr2        period
+///              period/2
+/// ```
+///
+/// ```
+/// let mut center_aligned = CenterAligned::new(&timer, cr1, cr2, &mut out1);
+/// center_aligned.set_symmetric_duty(&mut timer, &mut hr_control.control, 1_000);
+/// ```
+pub struct CenterAligned<TIM, PSCL> {
+    cr1: HrCr1<TIM, PSCL>,
+    cr2: HrCr2<TIM, PSCL>,
+}
+
+impl<TIM, PSCL> CenterAligned<TIM, PSCL>
+where
+    TIM: InstanceX,
+    PSCL: HrtimPrescaler,
+{
+    /// Set `out` on compare match with `cr1` and reset it on compare match with `cr2`
+    ///
+    /// The duty is initially 0.
+    ///
+    /// Panic if preload is not enabled for the timer, see [`crate::HrPwmBuilder::preload`],
+    /// or if the timer is not in [`HrCountingDirection::Up`] mode
+    pub fn new<T, O>(timer: &T, cr1: HrCr1<TIM, PSCL>, cr2: HrCr2<TIM, PSCL>, out: &mut O) -> Self
+    where
+        T: HrTimer<Timer = TIM, Prescaler = PSCL>,
+        O: HrOutput<TIM, PSCL>,
+    {
+        let tim = unsafe { &*TIM::ptr() };
+        assert!(
+            tim.cr().read().preen().bit_is_set(),
+            "Preload has to be enabled"
+        );
+        assert!(
+            timer.counting_mode() == HrCountingDirection::Up,
+            "Only for up counting mode"
+        );
+
+        out.enable_set_event(&cr1);
+        out.enable_rst_event(&cr2);
+
+        let mut this = Self { cr1, cr2 };
+        let period = this.period();
+        this.write(period / 2, period / 2);
+        this
+    }
+
+    /// Release the compare registers
+    ///
+    /// NOTE: The output keeps its set/reset events
+    pub fn free(self) -> (HrCr1<TIM, PSCL>, HrCr2<TIM, PSCL>) {
+        (self.cr1, self.cr2)
+    }
+
+    fn period(&self) -> u16 {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.perr().read().per().bits()
+    }
+
+    fn write(&mut self, cr1: u16, cr2: u16) {
+        self.cr1.set_duty(cr1);
+        self.cr2.set_duty(cr2);
+    }
+}
+
+impl<T, TIM, PSCL> SymmetricDuty<T> for CenterAligned<TIM, PSCL>
+where
+    T: HrTimer<Timer = TIM, Prescaler = PSCL>,
+    TIM: InstanceX,
+    PSCL: HrtimPrescaler,
+{
+    /// Set the on time, in ticks, centered around the middle of the period
+    ///
+    /// Both compare values are latched at the same update event. The on time is
+    /// saturated to the range allowed by the period and the prescaler, and shortened
+    /// symmetrically if needed to keep both compare values within the period.
+    ///
+    /// Returns the on time actually applied
+    fn set_symmetric_duty(&mut self, timer: &mut T, hr_control: &mut HrPwmCtrl, duty: u32) -> u32 {
+        let period = timer.get_period();
+        let duty = duty.min(u32::from(period)) as u16;
+
+        let cr1 = (period - duty) / 2;
+        let cr1 = if cr1 == 0 {
+            0
+        } else {
+            cr1.clamp(PSCL::MIN_CR, PSCL::MAX_CR)
+        };
+        // cr1 may have been moved later by the clamping above, pull cr2 in by the same amount to
+        // keep the pulse symmetric and never past the period where it would never fire
+        let cr2 = cr1
+            .saturating_add(duty)
+            .min(period.saturating_sub(cr1))
+            .clamp(cr1, PSCL::MAX_CR);
+
+        timer.disable_register_updates(hr_control);
+        self.write(cr1, cr2);
+        timer.enable_register_updates(hr_control);
+
+        u32::from(cr2 - cr1)
+    }
+
+    fn get_symmetric_duty(&self, _timer: &T) -> u32 {
+        u32::from(self.cr2.get_duty().saturating_sub(self.cr1.get_duty()))
+    }
+}
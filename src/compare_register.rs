@@ -1,14 +1,229 @@
 use core::marker::PhantomData;
 
+use fugit::{HertzU32, NanosDurationU32};
+
+use crate::control::HrPwmCtrl;
 use crate::ext::{Cmp, MasterExt};
 #[cfg(feature = "hrtim_v2")]
 use crate::pac::{HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME, HRTIM_TIMF};
-use crate::timer::{Instance, InstanceX};
-use crate::{pac::HRTIM_MASTER, DacStepTrigger, NoDacTrigger};
+use crate::timer::{HrTimer, Instance, InstanceX};
+use crate::{pac::HRTIM_MASTER, DacStepTrigger, HrCountingDirection, HrtimPrescaler, NoDacTrigger};
 
 pub trait HrCompareRegister {
     fn get_duty(&self) -> u16;
     fn set_duty(&mut self, duty: u16);
+
+    /// Minimum allowed compare value for the timer's prescaler, see [`HrtimPrescaler::MIN_CR`]
+    ///
+    /// NOTE: 0 is also allowed for some compare registers, see [`Self::allows_zero`]
+    fn min_duty(&self) -> u16;
+
+    /// Maximum allowed compare value for the timer's prescaler, see [`HrtimPrescaler::MAX_CR`]
+    fn max_duty(&self) -> u16;
+
+    /// Is 0 an allowed compare value, only true for CR1 and CR3
+    fn allows_zero(&self) -> bool;
+
+    /// Set duty, returns an error without writing anything if `duty` is not allowed
+    ///
+    /// A compare value below [`Self::min_duty`] will never generate a compare event
+    fn try_set_duty(&mut self, duty: u16) -> Result<(), DutyError> {
+        if duty == 0 && self.allows_zero() {
+            // Ok
+        } else if duty < self.min_duty() {
+            return Err(DutyError::BelowMin);
+        } else if duty > self.max_duty() {
+            return Err(DutyError::AboveMax);
+        }
+
+        self.set_duty(duty);
+        Ok(())
+    }
+
+    /// Set duty, saturated to the allowed range
+    ///
+    /// Returns the value actually written
+    fn set_duty_clamped(&mut self, duty: u16) -> u16 {
+        let duty = if duty == 0 && self.allows_zero() {
+            0
+        } else {
+            duty.clamp(self.min_duty(), self.max_duty())
+        };
+
+        self.set_duty(duty);
+        duty
+    }
+
+    /// Set duty as a fraction of the current period of `timer`, saturated to the allowed range
+    ///
+    /// The fraction is the part of the period during which the counter is below the compare
+    /// value. This is the same for both [`HrCountingDirection::Up`] and `UpDown`.
+    ///
+    /// Returns the value actually written
+    ///
+    /// ```
+    /// cr1.set_duty_fraction(&timer, 0.25);
+    /// cr1.set_duty_fraction(&timer, Q15(0x2000));
+    /// ```
+    fn set_duty_fraction<T: HrTimer, F: DutyFraction>(&mut self, timer: &T, fraction: F) -> u16
+    where
+        Self: Sized,
+    {
+        self.set_duty_clamped(fraction.of(timer.get_period()))
+    }
+
+    /// Set duty such that the counter is below the compare value for `on_time`, saturated to
+    /// the allowed range
+    ///
+    /// In [`HrCountingDirection::UpDown`] mode, the counter passes the compare value twice
+    /// every period, so only half as many ticks are needed.
+    ///
+    /// `f_hrtim` is the frequency of the HRTIM clock, f_HRTIM
+    ///
+    /// Returns the value actually written
+    fn set_on_time<T: HrTimer>(
+        &mut self,
+        timer: &T,
+        on_time: NanosDurationU32,
+        f_hrtim: HertzU32,
+    ) -> u16
+    where
+        Self: Sized,
+    {
+        // on_time / t_tick = on_time * f_HRTIM * multiplier / prescaler
+        //
+        // Saturate on overflow, a few seconds at f_HRTIM = 170MHz, since the result is way above
+        // what fits in the compare register anyway
+        let num = u64::from(on_time.ticks())
+            .checked_mul(u64::from(f_hrtim.raw()))
+            .and_then(|n| n.checked_mul(crate::HRCK_MULTIPLIER))
+            .unwrap_or(u64::MAX);
+        let den = match timer.counting_mode() {
+            HrCountingDirection::Up => 1,
+            #[cfg(feature = "hrtim_v2")]
+            HrCountingDirection::UpDown => 2,
+        } * u64::from(T::Prescaler::VALUE)
+            * 1_000_000_000;
+        let ticks = num.saturating_add(den / 2) / den;
+
+        self.set_duty_clamped(ticks.min(u64::from(u16::MAX)) as u16)
+    }
+}
+
+/// Symmetric PWM, with the on time centered on the middle of the effective period
+///
+/// Implemented for compare registers, using [`HrCountingDirection::UpDown`] mode, and for
+/// [`crate::center_aligned::CenterAligned`], which emulates it in up counting mode. This allows
+/// the same control code to run on devices with and without up-down counting.
+///
+/// The on time is in ticks out of the effective period, which is twice the period in
+/// `UpDown` mode, see [`HrTimer::get_period`].
+///
+/// ```
+/// fn control<S: SymmetricDuty<T>, T: HrTimer>(pwm: &mut S, timer: &mut T, hr_control: &mut HrPwmCtrl) {
+///     pwm.set_symmetric_duty(timer, hr_control, 1_000);
+/// }
+/// ```
+pub trait SymmetricDuty<T: HrTimer> {
+    /// Set the on time in ticks, saturated to the range allowed by the period and the prescaler
+    ///
+    /// Returns the on time actually applied
+    fn set_symmetric_duty(&mut self, timer: &mut T, hr_control: &mut HrPwmCtrl, duty: u32) -> u32;
+
+    /// Current on time in ticks
+    fn get_symmetric_duty(&self, timer: &T) -> u32;
+}
+
+/// The on time is the time the counter is above the compare value, which is when an output
+/// with this compare as its set event is active.
+///
+/// In [`HrCountingDirection::UpDown`] mode the set event acts as a reset event while counting
+/// down, so the on time is centered on the counter reaching the period. In
+/// [`HrCountingDirection::Up`] mode the on time ends at the period, see
+/// [`crate::center_aligned::CenterAligned`] to center it in that mode.
+impl<T, TIM, PSCL, CMP, S> SymmetricDuty<T> for HrCr<TIM, PSCL, CMP, S>
+where
+    T: HrTimer<Timer = TIM, Prescaler = PSCL>,
+    TIM: Instance,
+    PSCL: HrtimPrescaler,
+    CMP: CmpExt,
+    S: DacStepTrigger,
+{
+    fn set_symmetric_duty(&mut self, timer: &mut T, _hr_control: &mut HrPwmCtrl, duty: u32) -> u32 {
+        let period = u32::from(timer.get_period());
+        let crossings = crossings_per_period(timer);
+
+        // Only a single compare value, no need to disable register updates
+        let cmp = period - (duty / crossings).min(period);
+        let cmp = u32::from(self.set_duty_clamped(cmp as u16));
+
+        crossings * (period - cmp.min(period))
+    }
+
+    fn get_symmetric_duty(&self, timer: &T) -> u32 {
+        let period = u32::from(timer.get_period());
+        let cmp = u32::from(self.get_duty());
+
+        crossings_per_period(timer) * (period - cmp.min(period))
+    }
+}
+
+/// Number of times the counter passes each value every period
+fn crossings_per_period<T: HrTimer>(timer: &T) -> u32 {
+    match timer.counting_mode() {
+        HrCountingDirection::Up => 1,
+        #[cfg(feature = "hrtim_v2")]
+        HrCountingDirection::UpDown => 2,
+    }
+}
+
+/// Fraction of a period used with [`HrCompareRegister::set_duty_fraction`]
+///
+/// Implemented for `f32` in the range 0.0..=1.0, [`Q15`] and [`Q16`]
+pub trait DutyFraction {
+    /// This fraction of `max`, saturated to the range 0..=max
+    fn of(self, max: u16) -> u16;
+}
+
+/// Signed fixed point fraction with 15 fractional bits, negative values saturate to 0
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Q15(pub i16);
+
+/// Unsigned fixed point fraction with 16 fractional bits
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Q16(pub u16);
+
+impl DutyFraction for Q15 {
+    fn of(self, max: u16) -> u16 {
+        let fraction = self.0.max(0) as u32;
+        ((u32::from(max) * fraction + (1 << 14)) >> 15) as u16
+    }
+}
+
+impl DutyFraction for Q16 {
+    fn of(self, max: u16) -> u16 {
+        ((u32::from(max) * u32::from(self.0) + (1 << 15)) >> 16) as u16
+    }
+}
+
+impl DutyFraction for f32 {
+    fn of(self, max: u16) -> u16 {
+        // NaN saturates to 0
+        let fraction = self.clamp(0.0, 1.0);
+        (fraction * f32::from(max) + 0.5) as u16
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DutyError {
+    /// Duty below [`HrCompareRegister::min_duty`]
+    BelowMin,
+
+    /// Duty above [`HrCompareRegister::max_duty`]
+    AboveMax,
 }
 
 pub struct Cmp1;
@@ -47,7 +262,7 @@ use super::adc_trigger::{
     AdcTrigger6810 as Adc6810,
 };
 
-impl<TIM: Instance, PSCL, CMP: CmpExt, S: DacStepTrigger> HrCompareRegister
+impl<TIM: Instance, PSCL: HrtimPrescaler, CMP: CmpExt, S: DacStepTrigger> HrCompareRegister
     for HrCr<TIM, PSCL, CMP, S>
 {
     fn get_duty(&self) -> u16 {
@@ -60,6 +275,18 @@ impl<TIM: Instance, PSCL, CMP: CmpExt, S: DacStepTrigger> HrCompareRegister
 
         tim.cmpr(CMP::CMP).write(|w| unsafe { w.cmp().bits(duty) });
     }
+
+    fn min_duty(&self) -> u16 {
+        PSCL::MIN_CR
+    }
+
+    fn max_duty(&self) -> u16 {
+        PSCL::MAX_CR
+    }
+
+    fn allows_zero(&self) -> bool {
+        matches!(CMP::CMP, Cmp::Cmp1 | Cmp::Cmp3)
+    }
 }
 
 macro_rules! hrtim_cr_helper {
@@ -3,10 +3,11 @@ use crate::adc_trigger;
 #[cfg(feature = "hrtim_v2")]
 use crate::fault::FltMonitor6;
 use crate::fault::{
-    FltMonitor1, FltMonitor2, FltMonitor3, FltMonitor4, FltMonitor5, FltMonitorSys,
+    FaultStatus, FltMonitor1, FltMonitor2, FltMonitor3, FltMonitor4, FltMonitor5, FltMonitorSys,
 };
 
-use crate::timer::{self, HrTimer};
+use crate::ext::TimExt;
+use crate::timer::{self, HrTimer, HrTimerSet, InstanceX, TimX};
 use crate::{pac, pac::HRTIM_COMMON};
 
 use super::{external_event::EevInputs, fault::FaultInputs};
@@ -309,6 +310,138 @@ impl HrPwmCtrl {
         let master = unsafe { pac::HRTIM_MASTER::steal() };
         master.cr().modify(|_, w| p(W(w)).0);
     }
+
+    /// Update registers of multiple timers as one transaction
+    ///
+    /// The register updates of all the timers in `timers` are disabled, with a single write,
+    /// while `f` runs. They are then enabled again, also with a single write, so every
+    /// timer latches all the new values on its next update event.
+    ///
+    /// ```
+    /// hr_control.update_transaction((&mut timer_a, &mut timer_b), |(timer_a, timer_b)| {
+    ///     timer_a.set_period(period);
+    ///     timer_b.set_period(period);
+    ///     cr1_a.set_duty(duty);
+    ///     cr1_b.set_duty(duty);
+    /// });
+    /// ```
+    pub fn update_transaction<S: HrTimerSet, R>(
+        &mut self,
+        mut timers: S,
+        f: impl FnOnce(&mut S) -> R,
+    ) -> R {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        let bits = timers.udis_bits();
+
+        // SAFETY: Only the TxUDIS bits of the timers in the set are touched
+        common
+            .cr1()
+            .modify(|r, w| unsafe { w.bits(r.bits() | bits) });
+
+        let result = f(&mut timers);
+
+        common
+            .cr1()
+            .modify(|r, w| unsafe { w.bits(r.bits() & !bits) });
+
+        result
+    }
+
+    /// Read the fault flags of all fault monitors, including the system fault, at once
+    pub fn fault_status(&self) -> FaultStatus {
+        FaultStatus::read()
+    }
+
+    /// Clear the fault interrupt flags of all the specified faults at once
+    ///
+    /// This will *NOT* resume normal PWM operation. The affected outputs need to be re-enabled to resume operation;
+    /// This will do nothing for faults that are still active.
+    pub fn clear_faults(&mut self, faults: FaultStatus) {
+        faults.clear();
+    }
+
+    /// Read which fault inputs and timer configurations are locked
+    ///
+    /// The lock bits survive a software reset, use this to check what can still be configured
+    pub fn lock_status(&self) -> LockStatus {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        let fltinr1 = common.fltinr1().read();
+        let fltinr2 = common.fltinr2().read();
+
+        let locks = [
+            (fltinr1.flt1lck().bit(), FaultStatus::FLT1),
+            (fltinr1.flt2lck().bit(), FaultStatus::FLT2),
+            (fltinr1.flt3lck().bit(), FaultStatus::FLT3),
+            (fltinr1.flt4lck().bit(), FaultStatus::FLT4),
+            (fltinr2.flt5lck().bit(), FaultStatus::FLT5),
+            #[cfg(feature = "hrtim_v2")]
+            (fltinr2.flt6lck().bit(), FaultStatus::FLT6),
+        ];
+        let mut fault_inputs = FaultStatus::empty();
+        for (is_locked, input) in locks {
+            if is_locked {
+                fault_inputs |= input;
+            }
+        }
+
+        let mut status = LockStatus {
+            fault_inputs,
+            timer_faults: 0,
+            timer_deadtimes: 0,
+        };
+
+        status.read_timer::<pac::HRTIM_TIMA>();
+        status.read_timer::<pac::HRTIM_TIMB>();
+        status.read_timer::<pac::HRTIM_TIMC>();
+        status.read_timer::<pac::HRTIM_TIMD>();
+        status.read_timer::<pac::HRTIM_TIME>();
+        #[cfg(feature = "hrtim_v2")]
+        status.read_timer::<pac::HRTIM_TIMF>();
+
+        status
+    }
+}
+
+/// Which configurations are locked, see [`HrPwmCtrl::lock_status`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LockStatus {
+    fault_inputs: FaultStatus,
+    timer_faults: u8,
+    timer_deadtimes: u8,
+}
+
+impl LockStatus {
+    /// The fault inputs whose configuration is locked, FLTxLCK
+    ///
+    /// [`FaultStatus::SYS`] is never set since the system fault has no lock bit
+    pub fn fault_inputs(&self) -> FaultStatus {
+        self.fault_inputs
+    }
+
+    /// Is the fault configuration of the timer locked, FLTLCK
+    pub fn timer_faults(&self, timer: TimX) -> bool {
+        self.timer_faults & (1 << timer as u8) != 0
+    }
+
+    /// Is any part of the deadtime configuration of the timer locked, DTxLK/DTxSLK
+    pub fn timer_deadtime(&self, timer: TimX) -> bool {
+        self.timer_deadtimes & (1 << timer as u8) != 0
+    }
+
+    fn read_timer<TIM: InstanceX>(&mut self) {
+        let tim = unsafe { &*TIM::ptr() };
+        let dtr = tim.dtr().read();
+        let bit = 1 << TIM::T_X as u8;
+
+        if tim.fltr().read().fltlck().bit() {
+            self.timer_faults |= bit;
+        }
+
+        if dtr.dtrlk().bit() || dtr.dtrslk().bit() || dtr.dtflk().bit() || dtr.dtfslk().bit() {
+            self.timer_deadtimes |= bit;
+        }
+    }
 }
 
 /// Used as a token to guarantee unique access to resources common to multiple timers
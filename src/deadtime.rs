@@ -1,3 +1,10 @@
+use core::marker::PhantomData;
+
+use fugit::{HertzU32, NanosDurationU32};
+
+use crate::ext::TimExt;
+use crate::timer::InstanceX;
+
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[derive(Copy, Clone, Debug)]
 pub struct DeadtimeConfig {
@@ -18,6 +25,59 @@ pub struct DeadtimeConfig {
 }
 
 impl DeadtimeConfig {
+    /// Positive rising and falling deadtime of the specified durations
+    ///
+    /// The finest prescaler able to fit both durations is selected, see RM0440 Table 221
+    /// 'Deadtime resolution and max absolute values'. The durations are rounded up to a
+    /// multiple of the resolution, so the achieved deadtime is never shorter than requested.
+    /// Use [`Self::deadtime_rising_ps`] and [`Self::deadtime_falling_ps`] to get the achieved
+    /// deadtime.
+    ///
+    /// `f_hrtim` is the frequency of the HRTIM clock, f_HRTIM
+    ///
+    /// ```
+    /// let deadtime = DeadtimeConfig::from_duration(100.nanos(), 150.nanos(), 170.MHz())?;
+    /// ```
+    pub fn from_duration(
+        rising: NanosDurationU32,
+        falling: NanosDurationU32,
+        f_hrtim: HertzU32,
+    ) -> Result<Self, DeadtimeError> {
+        for prescaler in DeadtimePrescaler::ALL {
+            let (Some(rising), Some(falling)) = (
+                prescaler.ticks(rising, f_hrtim),
+                prescaler.ticks(falling, f_hrtim),
+            ) else {
+                continue;
+            };
+
+            return Ok(Self {
+                prescaler,
+                deadtime_rising_value: rising,
+                deadtime_rising_sign: false,
+                deadtime_falling_value: falling,
+                deadtime_falling_sign: false,
+            });
+        }
+
+        Err(DeadtimeError::TooLong)
+    }
+
+    /// Resolution of the deadtime in picoseconds
+    pub fn resolution_ps(&self, f_hrtim: HertzU32) -> u32 {
+        self.prescaler.resolution_ps(f_hrtim)
+    }
+
+    /// Absolute value of the rising deadtime in picoseconds
+    pub fn deadtime_rising_ps(&self, f_hrtim: HertzU32) -> u32 {
+        self.prescaler.to_ps(self.deadtime_rising_value, f_hrtim)
+    }
+
+    /// Absolute value of the falling deadtime in picoseconds
+    pub fn deadtime_falling_ps(&self, f_hrtim: HertzU32) -> u32 {
+        self.prescaler.to_ps(self.deadtime_falling_value, f_hrtim)
+    }
+
     /// See RM0440 Table 221 'Deadtime resolution and max absolute values'
     pub fn prescaler(mut self, value: DeadtimePrescaler) -> Self {
         self.prescaler = value;
@@ -79,3 +139,163 @@ pub enum DeadtimePrescaler {
     ThrtimMul8 = 0b110,
     ThrtimMul16 = 0b111,
 }
+
+impl DeadtimePrescaler {
+    /// All prescalers, from the finest to the coarsest resolution
+    const ALL: [Self; 8] = [
+        Self::ThrtimDiv8,
+        Self::ThrtimDiv4,
+        Self::ThrtimDiv2,
+        Self::Thrtim,
+        Self::ThrtimMul2,
+        Self::ThrtimMul4,
+        Self::ThrtimMul8,
+        Self::ThrtimMul16,
+    ];
+
+    /// Resolution of the deadtime in picoseconds, t_DTG = 2^DTPRSC * (t_HRTIM / 8)
+    pub fn resolution_ps(self, f_hrtim: HertzU32) -> u32 {
+        self.to_ps(1, f_hrtim)
+    }
+
+    /// Max absolute deadtime in picoseconds, 511 * t_DTG
+    pub fn max_deadtime_ps(self, f_hrtim: HertzU32) -> u32 {
+        self.to_ps(MAX_DEADTIME_VALUE, f_hrtim)
+    }
+
+    fn to_ps(self, value: u16, f_hrtim: HertzU32) -> u32 {
+        let ps =
+            (u64::from(value) * (1_000_000_000_000 << self as u8)) / (8 * u64::from(f_hrtim.raw()));
+        ps as u32
+    }
+
+    /// Number of deadtime ticks to cover `duration`, rounded up so that the deadtime is never
+    /// shorter than requested. `None` if it does not fit in 9 bits
+    fn ticks(self, duration: NanosDurationU32, f_hrtim: HertzU32) -> Option<u16> {
+        // duration / t_DTG = duration * 8 * f_HRTIM / 2^DTPRSC
+        let num = u64::from(duration.ticks()).checked_mul(8 * u64::from(f_hrtim.raw()))?;
+        let den = 1_000_000_000 << self as u8;
+        let ticks = num.div_ceil(den);
+
+        (ticks <= u64::from(MAX_DEADTIME_VALUE)).then_some(ticks as u16)
+    }
+}
+
+/// Max deadtime value, 9 bits
+const MAX_DEADTIME_VALUE: u16 = (1 << 9) - 1;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeadtimeError {
+    /// The deadtime does not fit in 9 bits, even with the coarsest prescaler for
+    /// [`DeadtimeConfig::from_duration`]
+    TooLong,
+
+    /// The deadtime configuration is locked, see [`crate::HrPwmBuilder::deadtime_lock`]
+    Locked,
+
+    /// Deadtime is not enabled for the timer, see [`crate::HrPwmBuilder::deadtime`]
+    NotEnabled,
+}
+
+/// Runtime access to the deadtime of a timer
+///
+/// Only usable if the timer was configured with a deadtime and `deadtime_lock(false)`, all
+/// setters will return [`DeadtimeError::NotEnabled`] or [`DeadtimeError::Locked`] otherwise.
+///
+/// ```
+/// let HrParts { mut deadtime, .. } = dp
+///     .HRTIM_TIMA
+///     .pwm_advanced(pin_a, pin_b)
+///     .deadtime(DeadtimeConfig::default())
+///     .deadtime_lock(false)
+///     .finalize(&mut hr_control);
+///
+/// // Adjust for ZVS
+/// deadtime.set_rising(120, false)?;
+/// ```
+pub struct DeadtimeHandle<TIM> {
+    _x: PhantomData<TIM>,
+}
+
+impl<TIM: InstanceX> DeadtimeHandle<TIM> {
+    /// Is any part of the deadtime locked, the rising or falling value or sign
+    pub fn is_locked(&self) -> bool {
+        let tim = unsafe { &*TIM::ptr() };
+        let dtr = tim.dtr().read();
+
+        dtr.dtrlk().bit() || dtr.dtrslk().bit() || dtr.dtflk().bit() || dtr.dtfslk().bit()
+    }
+
+    /// Is deadtime enabled for the timer, see [`crate::HrPwmBuilder::deadtime`]
+    pub fn is_enabled(&self) -> bool {
+        let tim = unsafe { &*TIM::ptr() };
+
+        tim.outr().read().dten().bit()
+    }
+
+    /// Set rising deadtime value and sign
+    ///
+    /// Returns [`DeadtimeError::TooLong`] if value can not fit in 9 bits and
+    /// [`DeadtimeError::Locked`] if the value, or the sign when changing it, is locked
+    pub fn set_rising(&mut self, value: u16, is_negative: bool) -> Result<(), DeadtimeError> {
+        self.check(value)?;
+
+        let tim = unsafe { &*TIM::ptr() };
+        let dtr = tim.dtr().read();
+        if dtr.dtrlk().bit() || (dtr.dtrslk().bit() && dtr.sdtr().bit() != is_negative) {
+            return Err(DeadtimeError::Locked);
+        }
+
+        // SAFETY: value is checked above
+        tim.dtr()
+            .modify(|_r, w| unsafe { w.dtr().bits(value).sdtr().bit(is_negative) });
+
+        Ok(())
+    }
+
+    /// Set falling deadtime value and sign
+    ///
+    /// Returns [`DeadtimeError::TooLong`] if value can not fit in 9 bits and
+    /// [`DeadtimeError::Locked`] if the value, or the sign when changing it, is locked
+    pub fn set_falling(&mut self, value: u16, is_negative: bool) -> Result<(), DeadtimeError> {
+        self.check(value)?;
+
+        let tim = unsafe { &*TIM::ptr() };
+        let dtr = tim.dtr().read();
+        if dtr.dtflk().bit() || (dtr.dtfslk().bit() && dtr.sdtf().bit() != is_negative) {
+            return Err(DeadtimeError::Locked);
+        }
+
+        // SAFETY: value is checked above
+        tim.dtr()
+            .modify(|_r, w| unsafe { w.dtf().bits(value).sdtf().bit(is_negative) });
+
+        Ok(())
+    }
+
+    fn check(&self, value: u16) -> Result<(), DeadtimeError> {
+        if value > MAX_DEADTIME_VALUE {
+            Err(DeadtimeError::TooLong)
+        } else if !self.is_enabled() {
+            Err(DeadtimeError::NotEnabled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Lock the deadtime configuration until the next system reset
+    pub fn lock(self) {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.dtr().modify(|_r, w| {
+            w.dtflk()
+                .set_bit()
+                .dtfslk()
+                .set_bit()
+                .dtrlk()
+                .set_bit()
+                .dtrslk()
+                .set_bit()
+        });
+    }
+}
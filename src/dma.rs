@@ -0,0 +1,171 @@
+use core::marker::PhantomData;
+
+use crate::compare_register::CmpExt;
+use crate::ext::{MasterExt, TimExt};
+use crate::timer::{self, ChExt, DmaChannel, HrTimerInterrupt, Instance, InstanceX};
+
+/// HAL agnostic description of a peripheral end of a DMA transfer
+///
+/// # Safety
+/// Only implement for types which guarantee that `address` points to a register
+/// that may be accessed by the DMA with the size of `Word`
+pub unsafe trait DmaPeripheral {
+    type Word;
+
+    /// Address of the peripheral register
+    fn address(&self) -> u32;
+}
+
+/// Peripheral that may be read by the DMA for peripheral-to-memory transfers
+///
+/// # Safety
+/// See [`DmaPeripheral`]
+pub unsafe trait DmaReadable: DmaPeripheral {}
+
+/// Peripheral that may be written by the DMA for memory-to-peripheral transfers
+///
+/// # Safety
+/// See [`DmaPeripheral`]
+pub unsafe trait DmaWritable: DmaPeripheral {}
+
+/// Timer register that may be accessed by the DMA
+pub trait DmaRegister<TIM> {
+    fn address() -> u32;
+}
+
+/// Timer register that may be read by the DMA
+pub trait DmaReadableRegister<TIM>: DmaRegister<TIM> {}
+
+/// Timer register that may be written by the DMA
+pub trait DmaWritableRegister<TIM>: DmaRegister<TIM> {}
+
+/// The timer's period register, PERxR
+pub struct Period;
+
+/// The timer's repetition register, REPxR
+pub struct Repetition;
+
+/// The timer's counter register, CNTxR
+pub struct Counter;
+
+/// One of the timer's compare registers, CMPyxR
+pub struct Compare<CMP>(PhantomData<CMP>);
+
+/// One of the timer's capture registers, CPTyxR
+pub struct Capture<CH>(PhantomData<CH>);
+
+impl<CMP> Default for Compare<CMP> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<CH> Default for Capture<CH> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<TIM: Instance> DmaRegister<TIM> for Period {
+    fn address() -> u32 {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.perr().as_ptr() as u32
+    }
+}
+
+impl<TIM: Instance> DmaRegister<TIM> for Repetition {
+    fn address() -> u32 {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.repr().as_ptr() as u32
+    }
+}
+
+impl<TIM: Instance> DmaRegister<TIM> for Counter {
+    fn address() -> u32 {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.cntr().as_ptr() as u32
+    }
+}
+
+impl<TIM: Instance, CMP: CmpExt> DmaRegister<TIM> for Compare<CMP> {
+    fn address() -> u32 {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.cmpr(CMP::CMP).as_ptr() as u32
+    }
+}
+
+impl<TIM: InstanceX, CH: ChExt> DmaRegister<TIM> for Capture<CH> {
+    fn address() -> u32 {
+        let tim = unsafe { &*TIM::ptr() };
+        tim.cptr(CH::CH).as_ptr() as u32
+    }
+}
+
+impl<TIM: Instance> DmaReadableRegister<TIM> for Period {}
+impl<TIM: Instance> DmaWritableRegister<TIM> for Period {}
+impl<TIM: Instance> DmaReadableRegister<TIM> for Repetition {}
+impl<TIM: Instance> DmaWritableRegister<TIM> for Repetition {}
+impl<TIM: Instance> DmaReadableRegister<TIM> for Counter {}
+impl<TIM: Instance> DmaWritableRegister<TIM> for Counter {}
+impl<TIM: Instance, CMP: CmpExt> DmaReadableRegister<TIM> for Compare<CMP> {}
+impl<TIM: Instance, CMP: CmpExt> DmaWritableRegister<TIM> for Compare<CMP> {}
+impl<TIM: InstanceX, CH: ChExt> DmaReadableRegister<TIM> for Capture<CH> {}
+
+/// DMA requests generated by a timer on one or more events, transferring to or from the register `REG`
+///
+/// ```
+/// // Update the duty cycle from a memory buffer on every repetition event
+/// let request = dma_channel.into_dma_request(Compare::<Cmp1>::default(), &[HrTimerInterrupt::Repetition]);
+/// let address = request.address(); // Hand over to the hal's DMA driver
+/// ```
+pub struct DmaRequest<TIM, REG> {
+    _x: PhantomData<(TIM, REG)>,
+    event_bits: u32,
+}
+
+impl<TIM: Instance> DmaChannel<TIM> {
+    /// Generate DMA requests every time any of the specified `events` occurs
+    ///
+    /// The DMA requests are enabled using the DMA enable bits corresponding to the
+    /// interrupts in DIER.
+    ///
+    /// Panic if any of the events are not available for this timer, see [`HrTimerInterrupt::is_available`]
+    pub fn into_dma_request<REG: DmaRegister<TIM>>(
+        self,
+        _register: REG,
+        events: &[HrTimerInterrupt],
+    ) -> DmaRequest<TIM, REG> {
+        let event_bits = events.iter().fold(0, |bits, event| {
+            assert!(event.is_available(TIM::TIMX));
+            bits | event.bits()
+        });
+
+        // The DMA enable bits are 16 bits above the interrupt enable bits
+        timer::modify_dier::<TIM>(|bits| bits | (event_bits << 16));
+
+        DmaRequest {
+            _x: PhantomData,
+            event_bits,
+        }
+    }
+}
+
+impl<TIM: Instance, REG: DmaRegister<TIM>> DmaRequest<TIM, REG> {
+    /// Stop generating DMA requests and release the DMA channel
+    pub fn free(self) -> DmaChannel<TIM> {
+        timer::modify_dier::<TIM>(|bits| bits & !(self.event_bits << 16));
+
+        unsafe { DmaChannel::new() }
+    }
+}
+
+unsafe impl<TIM: Instance, REG: DmaRegister<TIM>> DmaPeripheral for DmaRequest<TIM, REG> {
+    type Word = u32;
+
+    fn address(&self) -> u32 {
+        REG::address()
+    }
+}
+
+unsafe impl<TIM: Instance, REG: DmaReadableRegister<TIM>> DmaReadable for DmaRequest<TIM, REG> {}
+unsafe impl<TIM: Instance, REG: DmaWritableRegister<TIM>> DmaWritable for DmaRequest<TIM, REG> {}
@@ -76,7 +76,12 @@ pub trait MasterExt {
 }
 
 pub trait TimExt:
-    MasterExt<ISRrs = tima::isr::ISRrs, ICRrs = tima::icr::ICRrs, DIERrs = tima::dier::DIERrs>
+    MasterExt<
+    ISRrs = tima::isr::ISRrs,
+    ICRrs = tima::icr::ICRrs,
+    DIERrs = tima::dier::DIERrs,
+    CRrs: reg::TimCrR + reg::TimCrW,
+>
 {
     fn cmp1cr(&self) -> &tima::CMP1CR;
     fn cpt1r(&self) -> &tima::CPT1R;
@@ -1,6 +1,10 @@
 #[cfg(feature = "hrtim_v2")]
 use crate::control::HrPwmControl;
+use crate::output::HrOutputSet;
 use crate::pac::HRTIM_COMMON;
+use crate::timer::HrTimer;
+#[cfg(feature = "hrtim_v2")]
+use crate::LockError;
 
 use super::control::HrPwmCtrl;
 
@@ -8,6 +12,11 @@ use super::control::HrPwmCtrl;
 pub trait FaultMonitor {
     fn enable_interrupt(&mut self, hr_control: &mut HrPwmCtrl);
 
+    fn disable_interrupt(&mut self, hr_control: &mut HrPwmCtrl);
+
+    /// Returns true if the fault interrupt is enabled
+    fn is_interrupt_enabled(&self) -> bool;
+
     /// Returns true if a fault is preventing PWM output
     fn is_fault_active(&self) -> bool;
 
@@ -38,6 +47,119 @@ pub enum FaultAction {
 /// Only implement for actual fault sources with correct `ENABLE_BITS`
 pub unsafe trait FaultSource: Copy {
     const ENABLE_BITS: u8;
+
+    /// The fault monitor for this fault source
+    type Monitor: FaultMonitor;
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultRecoveryError {
+    /// The fault triggered again before the outputs could be re-enabled
+    /// or right after they were re-enabled
+    Retriggered,
+}
+
+/// Helper for safely resuming PWM operation after a fault
+///
+/// Ties a fault source to the set of outputs that are configured with it,
+/// see [`crate::HrPwmBuilder::with_fault_source`].
+///
+/// ```
+/// let mut recovery = FaultRecovery::new(&mut hr_control.fault_3, &fault_source3, (&mut out1, &mut out2));
+///
+/// if recovery.is_fault_active() {
+///     match recovery.recover_at_period(&timer) {
+///         Ok(()) => defmt::info!("Outputs re-enabled"),
+///         Err(FaultRecoveryError::Retriggered) => defmt::info!("Fault triggered again"),
+///     }
+/// }
+/// ```
+pub struct FaultRecovery<'a, M, O> {
+    monitor: &'a mut M,
+    outputs: O,
+}
+
+impl<'a, M: FaultMonitor, O: HrOutputSet> FaultRecovery<'a, M, O> {
+    /// Create a new recovery helper
+    ///
+    /// `outputs` should be exactly the outputs whose timers were configured with `_source`
+    pub fn new<FS: FaultSource<Monitor = M>>(monitor: &'a mut M, _source: &FS, outputs: O) -> Self {
+        FaultRecovery { monitor, outputs }
+    }
+
+    /// Returns true if a fault is preventing PWM output
+    pub fn is_fault_active(&self) -> bool {
+        self.monitor.is_fault_active()
+    }
+
+    /// Wait for the fault input to become inactive, clear the fault and re-enable the outputs
+    ///
+    /// NOTE: This blocks for as long as the fault input is active
+    pub fn recover(&mut self) -> Result<(), FaultRecoveryError> {
+        self.wait_for_inactive_fault();
+        self.enable_outputs()
+    }
+
+    /// Wait for the fault input to become inactive, then wait for the next period of `timer`
+    /// before clearing the fault and re-enabling the outputs
+    ///
+    /// NOTE: This blocks for as long as the fault input is active.
+    /// `timer` needs to be running, or this will block forever
+    ///
+    /// Panic if `timer` is not in [`crate::HrCountingDirection::Up`] mode, the start of the
+    /// period is detected by the counter going down which happens mid-period when up-down counting
+    pub fn recover_at_period<T: HrTimer>(&mut self, timer: &T) -> Result<(), FaultRecoveryError> {
+        assert!(
+            timer.counting_mode() == crate::HrCountingDirection::Up,
+            "Only for up counting mode"
+        );
+
+        self.wait_for_inactive_fault();
+
+        // Wait for the counter to roll over or reset
+        let mut previous = timer.get_counter_value();
+        loop {
+            let counter = timer.get_counter_value();
+            if counter < previous {
+                break;
+            }
+            previous = counter;
+        }
+
+        self.enable_outputs()
+    }
+
+    /// Release the monitor and the outputs
+    pub fn free(self) -> (&'a mut M, O) {
+        (self.monitor, self.outputs)
+    }
+
+    fn wait_for_inactive_fault(&mut self) {
+        // Clearing the fault flag does nothing while the fault is still active
+        loop {
+            self.monitor.clear_fault();
+            if !self.monitor.is_fault_active() {
+                break;
+            }
+        }
+    }
+
+    fn enable_outputs(&mut self) -> Result<(), FaultRecoveryError> {
+        self.monitor.clear_fault();
+        if self.monitor.is_fault_active() {
+            return Err(FaultRecoveryError::Retriggered);
+        }
+
+        self.outputs.enable_all();
+
+        // The hardware disables the outputs again if the fault triggers
+        if self.monitor.is_fault_active() || !self.outputs.is_running() {
+            return Err(FaultRecoveryError::Retriggered);
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "stm32g4")]
@@ -50,6 +172,9 @@ pub struct SourceBuilder<I> {
 
     /// FLTxF[3:0]
     filter_bits: u8,
+
+    /// FLTxLCK
+    lock: bool,
 }
 
 #[cfg(feature = "stm32g4")]
@@ -63,6 +188,7 @@ impl<I> SourceBuilder<I> {
             src_bits,
             is_active_high: false,
             filter_bits: 0b0000,
+            lock: true,
         }
     }
 }
@@ -71,7 +197,7 @@ impl<I> SourceBuilder<I> {
 macro_rules! impl_faults {
     ($(
         $input:ident => $source:ident:
-            $enable_bits:literal,
+            $enable_bits:literal, $monitor:ident,
             $fltinrZ:ident, $fltWsrc_0:ident, $fltWsrc_1:ident, $fltWp:ident, $fltWf:ident, $fltWe:ident, $fltWlck:ident,
     )+) => {$(
         // This should NOT be Copy/Clone
@@ -84,8 +210,10 @@ macro_rules! impl_faults {
         pub struct $source;
 
         impl SourceBuilder<$input> {
+            /// NOTE: A fault input locked from before a software reset silently keeps its old
+            /// configuration, use `try_finalize` to get an error instead
             pub fn finalize(self, _control: &mut HrPwmControl) -> $source {
-                let SourceBuilder{ _input, src_bits, is_active_high, filter_bits } = self;
+                let SourceBuilder{ _input, src_bits, is_active_high, filter_bits, lock } = self;
 
                 // Setup fault source
                 unsafe {
@@ -100,17 +228,39 @@ macro_rules! impl_faults {
                     );
 
                     // ... and lock configuration
-                    common.$fltinrZ().modify(|_r, w| w.$fltWlck().set_bit());
+                    if lock {
+                        common.$fltinrZ().modify(|_r, w| w.$fltWlck().set_bit());
+                    }
                 }
 
                 $source
             }
 
+            /// Same as `finalize` but returns an error, without writing anything, if the
+            /// configuration of the fault input is still locked from before a software reset
+            pub fn try_finalize(self, control: &mut HrPwmControl) -> Result<$source, LockError> {
+                let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+                if common.$fltinrZ().read().$fltWlck().bit() {
+                    return Err(LockError::FaultInput);
+                }
+
+                Ok(self.finalize(control))
+            }
+
             pub fn polarity(mut self, polarity: super::Polarity) -> Self {
                 self.is_active_high = matches!(polarity, super::Polarity::ActiveHigh);
                 self
             }
 
+            /// Lock the configuration of the fault input after finalizing, this is the default
+            ///
+            /// Once locked, the configuration can not be changed until the next system reset
+            pub fn lock(mut self, lock: bool) -> Self {
+                self.lock = lock;
+                self
+            }
+
             // TODO: add more settings
             /* pub fn blanking(?) -> Self */
 
@@ -122,18 +272,19 @@ macro_rules! impl_faults {
 
         unsafe impl FaultSource for $source {
             const ENABLE_BITS: u8 = $enable_bits;
+            type Monitor = $monitor;
         }
     )+}
 }
 
 #[cfg(feature = "hrtim_v2")]
 impl_faults!(
-    FaultInput1 => FaultSource1: 0b000001, fltinr1, flt1src, flt1src_1, flt1p, flt1f, flt1e, flt1lck,
-    FaultInput2 => FaultSource2: 0b000010, fltinr1, flt2src, flt2src_1, flt2p, flt2f, flt2e, flt2lck,
-    FaultInput3 => FaultSource3: 0b000100, fltinr1, flt3src, flt3src_1, flt3p, flt3f, flt3e, flt3lck,
-    FaultInput4 => FaultSource4: 0b001000, fltinr1, flt4src, flt4src_1, flt4p, flt4f, flt4e, flt4lck,
-    FaultInput5 => FaultSource5: 0b010000, fltinr2, flt5src, flt5src_1, flt5p, flt5f, flt5e, flt5lck,
-    FaultInput6 => FaultSource6: 0b100000, fltinr2, flt6src, flt6src_1, flt6p, flt6f, flt6e, flt6lck,
+    FaultInput1 => FaultSource1: 0b000001, FltMonitor1, fltinr1, flt1src, flt1src_1, flt1p, flt1f, flt1e, flt1lck,
+    FaultInput2 => FaultSource2: 0b000010, FltMonitor2, fltinr1, flt2src, flt2src_1, flt2p, flt2f, flt2e, flt2lck,
+    FaultInput3 => FaultSource3: 0b000100, FltMonitor3, fltinr1, flt3src, flt3src_1, flt3p, flt3f, flt3e, flt3lck,
+    FaultInput4 => FaultSource4: 0b001000, FltMonitor4, fltinr1, flt4src, flt4src_1, flt4p, flt4f, flt4e, flt4lck,
+    FaultInput5 => FaultSource5: 0b010000, FltMonitor5, fltinr2, flt5src, flt5src_1, flt5p, flt5f, flt5e, flt5lck,
+    FaultInput6 => FaultSource6: 0b100000, FltMonitor6, fltinr2, flt6src, flt6src_1, flt6p, flt6f, flt6e, flt6lck,
 );
 
 pub struct FaultInputs {
@@ -230,6 +381,141 @@ pub enum FaultSamplingFilter {
     FltsDiv32N8 = 0b1111,
 }
 
+/// Snapshot of the fault flags for all fault monitors including the system fault
+///
+/// All flags are read from the ISR register at the same time, see [`HrPwmCtrl::fault_status`].
+///
+/// ```
+/// let status = hr_control.control.fault_status();
+/// if status.contains(FaultStatus::FLT1 | FaultStatus::SYS) {
+///     // Handle fault...
+/// }
+/// hr_control.control.clear_faults(status);
+/// ```
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FaultStatus(u8);
+
+impl FaultStatus {
+    pub const FLT1: Self = Self(1 << 0);
+    pub const FLT2: Self = Self(1 << 1);
+    pub const FLT3: Self = Self(1 << 2);
+    pub const FLT4: Self = Self(1 << 3);
+    pub const FLT5: Self = Self(1 << 4);
+    #[cfg(feature = "hrtim_v2")]
+    pub const FLT6: Self = Self(1 << 5);
+
+    /// System fault
+    pub const SYS: Self = Self(1 << 6);
+
+    /// No faults
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// All faults available on this device
+    pub const fn all() -> Self {
+        #[cfg(feature = "hrtim_v2")]
+        let bits = 0b111_1111;
+        #[cfg(any(feature = "hrtim_v1", feature = "hrtim_v1_1"))]
+        let bits = 0b101_1111;
+
+        Self(bits)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if all faults in `other` are also in `self`
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if any fault in `other` is also in `self`
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub(crate) fn read() -> Self {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+        // No need for exclusive access since this is a read only register
+        let isr = common.isr().read();
+
+        let mut status = Self::empty();
+        let flags = [
+            (isr.flt1().bit(), Self::FLT1),
+            (isr.flt2().bit(), Self::FLT2),
+            (isr.flt3().bit(), Self::FLT3),
+            (isr.flt4().bit(), Self::FLT4),
+            (isr.flt5().bit(), Self::FLT5),
+            #[cfg(feature = "hrtim_v2")]
+            (isr.flt6().bit(), Self::FLT6),
+            (isr.sysflt().bit(), Self::SYS),
+        ];
+        for (is_set, flag) in flags {
+            if is_set {
+                status |= flag;
+            }
+        }
+        status
+    }
+
+    pub(crate) fn clear(self) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+        // No need for exclusive access since this is a write only register
+        common.icr().write(|w| {
+            w.flt1c()
+                .bit(self.contains(Self::FLT1))
+                .flt2c()
+                .bit(self.contains(Self::FLT2))
+                .flt3c()
+                .bit(self.contains(Self::FLT3))
+                .flt4c()
+                .bit(self.contains(Self::FLT4))
+                .flt5c()
+                .bit(self.contains(Self::FLT5))
+                .sysfltc()
+                .bit(self.contains(Self::SYS));
+            #[cfg(feature = "hrtim_v2")]
+            w.flt6c().bit(self.contains(Self::FLT6));
+            w
+        });
+    }
+}
+
+impl core::ops::BitOr for FaultStatus {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitOrAssign for FaultStatus {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl core::ops::BitAnd for FaultStatus {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl core::ops::Not for FaultStatus {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0 & Self::all().0)
+    }
+}
+
 macro_rules! impl_flt_monitor {
     ($($t:ident: ($fltx:ident, $fltxc:ident, $fltxie:ident),)+) => {$(
         #[non_exhaustive]
@@ -238,7 +524,24 @@ macro_rules! impl_flt_monitor {
         impl FaultMonitor for $t {
             fn enable_interrupt(&mut self, _hr_control: &mut HrPwmCtrl) {
                 let common = unsafe { &*HRTIM_COMMON::ptr() };
-                common.ier().modify(|_r, w| w.$fltxie().set_bit());
+
+                // IER is also modified by the fault interrupt handler when using async
+                critical_section::with(|_| {
+                    common.ier().modify(|_r, w| w.$fltxie().set_bit());
+                });
+            }
+
+            fn disable_interrupt(&mut self, _hr_control: &mut HrPwmCtrl) {
+                let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+                critical_section::with(|_| {
+                    common.ier().modify(|_r, w| w.$fltxie().clear_bit());
+                });
+            }
+
+            fn is_interrupt_enabled(&self) -> bool {
+                let common = unsafe { &*HRTIM_COMMON::ptr() };
+                common.ier().read().$fltxie().bit()
             }
 
             fn is_fault_active(&self) -> bool {
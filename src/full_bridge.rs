@@ -0,0 +1,227 @@
+use crate::compare_register::{CmpExt, HrCompareRegister, HrCr, HrCr1};
+use crate::deadtime::DeadtimeConfig;
+use crate::event::{EventSource, TimerResetEventSource};
+use crate::fault::{FaultAction, FaultSource};
+use crate::output::{HrOut1, HrOut2, HrOutput, HrOutputSet, Output1Pin, Output2Pin};
+use crate::pac::HRTIM_MASTER;
+use crate::phase_shift::{Phase, PhaseShift};
+use crate::timer::{HrSlaveTimer, HrTimer, InstanceX};
+use crate::{HrPwmBuilder, HrtimPrescaler, PreloadSource};
+
+/// Configure a timer as one leg of a [`PhaseShiftedFullBridge`]
+///
+/// Sets the period, deadtime and fault source. Both outputs are forced inactive on fault and
+/// the registers are updated on master timer update.
+pub fn configure_leg<TIM, PSCL, P1, P2, FS>(
+    builder: HrPwmBuilder<TIM, PSCL, PreloadSource, P1, P2>,
+    period: u16,
+    deadtime: DeadtimeConfig,
+    fault_source: FS,
+) -> HrPwmBuilder<TIM, PSCL, PreloadSource, P1, P2>
+where
+    TIM: InstanceX,
+    PSCL: HrtimPrescaler,
+    P1: Output1Pin<TIM>,
+    P2: Output2Pin<TIM>,
+    FS: FaultSource,
+{
+    builder
+        .period(period)
+        .deadtime(deadtime)
+        .with_fault_source(fault_source)
+        .fault_action1(FaultAction::ForceInactive)
+        .fault_action2(FaultAction::ForceInactive)
+        .preload(PreloadSource::OnMasterTimerUpdate)
+}
+
+type BridgeOutputs<TA, TB, PSCL> = (
+    HrOut1<TA, PSCL>,
+    HrOut2<TA, PSCL>,
+    HrOut1<TB, PSCL>,
+    HrOut2<TB, PSCL>,
+);
+
+/// One leg of a [`PhaseShiftedFullBridge`]
+pub struct FullBridgeLeg<T: HrTimer> {
+    pub timer: T,
+    pub cr1: HrCr1<T::Timer, T::Prescaler>,
+    pub out1: HrOut1<T::Timer, T::Prescaler>,
+    pub out2: HrOut2<T::Timer, T::Prescaler>,
+}
+
+/// Phase shifted full bridge on two timers
+///
+/// Each leg is a complementary pair, with deadtime, running at a fixed 50% duty.
+/// Leg A is synchronized to the master period and leg B is phase shifted from it
+/// using a master compare register. All updates are transferred at the same time on
+/// the master repetition update.
+///
+/// ```text
+///                 |<-shift->|
+/// leg A out1  ----*         |    *---------*         *------
+///                 |         |    |         |         |
+///             ----*---------*----*         *---------*
+///
+/// leg B out1            *---------*         *---------*
+///                       |         |         |         |
+///             ----------*         *---------*         *----
+/// ```
+///
+/// ```
+/// let builder = configure_leg(dp.HRTIM_TIMA.pwm_advanced(pin_a1, pin_a2), period, deadtime, fault_source1);
+/// // Finalize builder for TIMA and TIMB, as well as the master with the same period
+/// ...
+/// let mut bridge = PhaseShiftedFullBridge::new(
+///     mtimer,
+///     mcr1,
+///     FullBridgeLeg { timer: timer_a, cr1: cr1_a, out1: out1_a, out2: out2_a },
+///     FullBridgeLeg { timer: timer_b, cr1: cr1_b, out1: out1_b, out2: out2_b },
+///     Phase::Degrees(0.0),
+/// );
+/// bridge.enable();
+/// bridge.set_phase_shift(Phase::Degrees(45.0));
+/// ```
+pub struct PhaseShiftedFullBridge<M: HrTimer, A: HrTimer, B: HrTimer, CMP> {
+    master: M,
+    timer_a: A,
+    timer_b: B,
+    shift: PhaseShift<M::Prescaler, CMP>,
+    phase: Phase,
+    cr_a: HrCr1<A::Timer, A::Prescaler>,
+    cr_b: HrCr1<B::Timer, B::Prescaler>,
+    outputs: BridgeOutputs<A::Timer, B::Timer, M::Prescaler>,
+}
+
+impl<M, A, B, PSCL, CMP> PhaseShiftedFullBridge<M, A, B, CMP>
+where
+    M: HrTimer<Timer = HRTIM_MASTER, Prescaler = PSCL>,
+    A: HrSlaveTimer<Prescaler = PSCL>,
+    B: HrSlaveTimer<Prescaler = PSCL>,
+    A::Timer: InstanceX,
+    B::Timer: InstanceX,
+    PSCL: HrtimPrescaler,
+    CMP: CmpExt,
+{
+    /// Take ownership of the master timer and the legs, configured with [`configure_leg`], wire
+    /// them up and set the initial phase shift
+    ///
+    /// Leg A is reset and set on the master period, leg B is reset and set on compare match
+    /// with `shift_cr`. Both legs are reset at half the master period. The outputs are left
+    /// disabled.
+    pub fn new(
+        mut master: M,
+        shift_cr: HrCr<HRTIM_MASTER, PSCL, CMP>,
+        leg_a: FullBridgeLeg<A>,
+        leg_b: FullBridgeLeg<B>,
+        phase: Phase,
+    ) -> Self
+    where
+        M: EventSource<A::Timer, PSCL> + TimerResetEventSource<A::Timer, PSCL>,
+        HrCr<HRTIM_MASTER, PSCL, CMP>:
+            EventSource<B::Timer, PSCL> + TimerResetEventSource<B::Timer, PSCL>,
+    {
+        let FullBridgeLeg {
+            timer: mut timer_a,
+            cr1: cr_a,
+            out1: mut out1_a,
+            out2: out2_a,
+        } = leg_a;
+        let FullBridgeLeg {
+            timer: mut timer_b,
+            cr1: cr_b,
+            out1: mut out1_b,
+            out2: out2_b,
+        } = leg_b;
+
+        timer_a.enable_reset_event(&master);
+        out1_a.enable_set_event(&master);
+        out1_a.enable_rst_event(&cr_a);
+
+        out1_b.enable_set_event(&shift_cr);
+        out1_b.enable_rst_event(&cr_b);
+
+        let period = master.get_period();
+        let shift = PhaseShift::new(&mut master, &mut timer_b, shift_cr, phase);
+
+        let mut this = Self {
+            master,
+            timer_a,
+            timer_b,
+            shift,
+            phase,
+            cr_a,
+            cr_b,
+            outputs: (out1_a, out2_a, out1_b, out2_b),
+        };
+        this.set_period(period);
+        this
+    }
+
+    /// Set the phase shift between leg A and leg B
+    ///
+    /// Returns the phase shift in ticks
+    pub fn set_phase_shift(&mut self, phase: Phase) -> u16 {
+        self.phase = phase;
+        self.shift.set_phase(phase)
+    }
+
+    /// Current phase shift in ticks
+    pub fn phase_shift_ticks(&self) -> u16 {
+        self.shift.phase_ticks()
+    }
+
+    /// Set the period of the master and both legs, keeping the phase shift
+    ///
+    /// A phase shift in ticks is kept in ticks, a phase shift in degrees is kept in degrees
+    pub fn set_period(&mut self, period: u16) {
+        self.master.set_period(period);
+        self.timer_a.set_period(period);
+        self.timer_b.set_period(period);
+
+        self.cr_a.set_duty_clamped(period / 2);
+        self.cr_b.set_duty_clamped(period / 2);
+
+        self.shift.set_phase(self.phase);
+    }
+
+    /// Enable all four outputs
+    pub fn enable(&mut self) {
+        self.outputs.enable_all();
+    }
+
+    /// Disable all four outputs
+    pub fn disable(&mut self) {
+        self.outputs.disable_all();
+    }
+
+    /// Release the master timer, its compare register and the legs
+    ///
+    /// NOTE: The events between the timers are kept
+    pub fn free(
+        self,
+    ) -> (
+        M,
+        HrCr<HRTIM_MASTER, PSCL, CMP>,
+        FullBridgeLeg<A>,
+        FullBridgeLeg<B>,
+    ) {
+        let (out1_a, out2_a, out1_b, out2_b) = self.outputs;
+
+        (
+            self.master,
+            self.shift.free(),
+            FullBridgeLeg {
+                timer: self.timer_a,
+                cr1: self.cr_a,
+                out1: out1_a,
+                out2: out2_a,
+            },
+            FullBridgeLeg {
+                timer: self.timer_b,
+                cr1: self.cr_b,
+                out1: out1_b,
+                out2: out2_b,
+            },
+        )
+    }
+}
@@ -0,0 +1,167 @@
+use crate::capture::{HrCaptCh1, HrCaptCh2};
+use crate::compare_register::{HrCompareRegister, HrCr1, HrCr2, HrCr3, HrCr4};
+use crate::deadtime::{DeadtimeConfig, DeadtimeHandle};
+use crate::event::EventSource;
+use crate::ext::{Chan, TimExt};
+use crate::output::{HrOut1, HrOut2, HrOutputSet};
+use crate::timer::{self, HrTim, InstanceX};
+use crate::{DacResetTrigger, DacStepTrigger, HrPwmBuilder, HrtimPrescaler, NoDacTrigger};
+
+/// Deadtime of a complementary pair, see [`HrPwmBuilder::complementary`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug)]
+pub enum BridgeDeadtime {
+    /// Insert deadtime between the two outputs
+    Enabled(DeadtimeConfig),
+
+    /// No deadtime at all, the outputs are switched at the exact same time
+    ///
+    /// NOTE: This will most likely cause shoot-through in a real half bridge, only use
+    /// this if the deadtime is handled elsewhere, for example by the gate driver
+    Disabled,
+}
+
+/// HrPwmBuilder for a complementary pair, see [`HrPwmBuilder::complementary`]
+pub struct HalfBridgeBuilder<
+    TIM,
+    PSCL,
+    PS,
+    P1,
+    P2,
+    DacRst: DacResetTrigger = NoDacTrigger,
+    DacStp: DacStepTrigger = NoDacTrigger,
+> {
+    pub(crate) builder: HrPwmBuilder<TIM, PSCL, PS, P1, P2, DacRst, DacStp>,
+}
+
+impl<TIM, PSCL, PS, P1, P2, DacRst, DacStp> HrPwmBuilder<TIM, PSCL, PS, P1, P2, DacRst, DacStp>
+where
+    TIM: InstanceX,
+    DacRst: DacResetTrigger,
+    DacStp: DacStepTrigger,
+{
+    /// Use output 1 and 2 as a complementary pair driving a half bridge
+    ///
+    /// Output 1 is set on the timer period and reset on compare match with cr1,
+    /// output 2 is the complement of output 1. The deadtime has to be specified,
+    /// use [`BridgeDeadtime::Disabled`] to explicitly opt out.
+    ///
+    /// ```
+    /// let HalfBridgeParts { mut timer, mut bridge, .. } = dp
+    ///     .HRTIM_TIMA
+    ///     .pwm_advanced(pin_a, pin_b)
+    ///     .period(0xFFFF)
+    ///     .complementary(BridgeDeadtime::Enabled(DeadtimeConfig::default()))
+    ///     .finalize(&mut hr_control);
+    ///
+    /// bridge.set_duty(0x4000);
+    /// bridge.enable();
+    /// timer.start(&mut hr_control.control);
+    /// ```
+    pub fn complementary(
+        mut self,
+        deadtime: BridgeDeadtime,
+    ) -> HalfBridgeBuilder<TIM, PSCL, PS, P1, P2, DacRst, DacStp> {
+        self.deadtime = match deadtime {
+            BridgeDeadtime::Enabled(deadtime) => Some(deadtime),
+            BridgeDeadtime::Disabled => None,
+        };
+
+        HalfBridgeBuilder { builder: self }
+    }
+}
+
+/// Set output 1 on the timer period and reset on cr1, output 2 is the inverse
+///
+/// NOTE: With deadtime enabled, output 2 is generated from output 1 by the hardware
+/// and its set/reset events are ignored
+pub(crate) fn wire_complementary<TIM: InstanceX, PSCL>() {
+    let period = <HrTim<TIM, PSCL, (), ()> as EventSource<TIM, PSCL>>::BITS;
+    let cmp1 = <HrCr1<TIM, PSCL> as EventSource<TIM, PSCL>>::BITS;
+
+    let tim = unsafe { &*TIM::ptr() };
+
+    // SAFETY: Only called while finalizing, before anyone else has access to the outputs
+    unsafe {
+        tim.set_r(Chan::Ch1).write(|w| w.bits(period));
+        tim.rst_r(Chan::Ch1).write(|w| w.bits(cmp1));
+        tim.set_r(Chan::Ch2).write(|w| w.bits(cmp1));
+        tim.rst_r(Chan::Ch2).write(|w| w.bits(period));
+    }
+}
+
+/// Same as [`crate::HrParts`] but with cr1, out1 and out2 bundled into a [`HalfBridge`]
+pub struct HalfBridgeParts<
+    TIM,
+    PSCL,
+    DacRst: DacResetTrigger = NoDacTrigger,
+    DacStp: DacStepTrigger = NoDacTrigger,
+> {
+    pub timer: HrTim<TIM, PSCL, HrCaptCh1<TIM, PSCL>, HrCaptCh2<TIM, PSCL>, DacRst>,
+    pub bridge: HalfBridge<TIM, PSCL, DacRst, DacStp>,
+
+    pub cr2: HrCr2<TIM, PSCL, DacStp>,
+    pub cr3: HrCr3<TIM, PSCL>,
+    pub cr4: HrCr4<TIM, PSCL>,
+
+    pub dma_channel: timer::DmaChannel<TIM>,
+    pub deadtime: DeadtimeHandle<TIM>,
+}
+
+/// Complementary pair of outputs driving a half bridge
+///
+/// Only obtainable through [`HrPwmBuilder::complementary`] so the deadtime is
+/// always either configured or explicitly disabled.
+pub struct HalfBridge<
+    TIM,
+    PSCL,
+    DacRst: DacResetTrigger = NoDacTrigger,
+    DacStp: DacStepTrigger = NoDacTrigger,
+> {
+    cr1: HrCr1<TIM, PSCL>,
+    out1: HrOut1<TIM, PSCL, DacRst, DacStp>,
+    out2: HrOut2<TIM, PSCL, DacRst, DacStp>,
+}
+
+impl<TIM, PSCL, DacRst, DacStp> HalfBridge<TIM, PSCL, DacRst, DacStp>
+where
+    TIM: InstanceX,
+    PSCL: HrtimPrescaler,
+    DacRst: DacResetTrigger,
+    DacStp: DacStepTrigger,
+{
+    /// Set the on time of output 1, saturated to the range allowed by the prescaler
+    ///
+    /// Returns the value actually written
+    pub fn set_duty(&mut self, duty: u16) -> u16 {
+        self.cr1.set_duty_clamped(duty)
+    }
+
+    pub fn get_duty(&self) -> u16 {
+        self.cr1.get_duty()
+    }
+
+    /// Enable both outputs at the exact same time
+    pub fn enable(&mut self) {
+        (&mut self.out1, &mut self.out2).enable_all();
+    }
+
+    /// Disable both outputs at the exact same time
+    pub fn disable(&mut self) {
+        (&mut self.out1, &mut self.out2).disable_all();
+    }
+
+    /// Release the compare register and the outputs
+    ///
+    /// NOTE: The outputs keep their set/reset events and the deadtime configuration
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        HrCr1<TIM, PSCL>,
+        HrOut1<TIM, PSCL, DacRst, DacStp>,
+        HrOut2<TIM, PSCL, DacRst, DacStp>,
+    ) {
+        (self.cr1, self.out1, self.out2)
+    }
+}
@@ -0,0 +1,351 @@
+use crate::fault::{FaultMonitor, FaultSource};
+use crate::output::HrOutputSet;
+
+/// Settings for [`Hiccup`] and [`HiccupController`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HiccupConfig {
+    pub(crate) off_periods: u16,
+    pub(crate) max_retries: u8,
+    pub(crate) settle_periods: u16,
+}
+
+impl HiccupConfig {
+    /// Number of periods to keep the outputs off after a fault, before trying again
+    ///
+    /// Panic if `periods` is 0
+    pub fn off_periods(mut self, periods: u16) -> Self {
+        assert!(periods > 0);
+
+        self.off_periods = periods;
+        self
+    }
+
+    /// Number of consecutive failed retries before latching off
+    pub fn max_retries(mut self, retries: u8) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Number of fault free periods after a retry for it to be considered successful
+    ///
+    /// Once successful, the count of consecutive failures is reset
+    pub fn settle_periods(mut self, periods: u16) -> Self {
+        self.settle_periods = periods;
+        self
+    }
+}
+
+impl Default for HiccupConfig {
+    fn default() -> Self {
+        Self {
+            off_periods: 100,
+            max_retries: 3,
+            settle_periods: 100,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HiccupState {
+    /// Outputs are enabled
+    Running,
+
+    /// Outputs are off, waiting for `remaining` more periods before trying again
+    Off { remaining: u16 },
+
+    /// Too many consecutive failures, outputs are off until reset
+    Latched,
+}
+
+/// What to do with the outputs after a call to [`HiccupController::step`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HiccupAction {
+    None,
+
+    /// Disable the outputs
+    Disable,
+
+    /// Clear the fault and enable the outputs
+    Enable,
+}
+
+/// Hardware independent state machine for hiccup mode
+///
+/// See [`Hiccup`] for a version that controls the actual outputs
+#[derive(Clone, Debug)]
+pub struct HiccupController {
+    config: HiccupConfig,
+    state: HiccupState,
+    failures: u8,
+    running_periods: u16,
+}
+
+impl HiccupController {
+    pub fn new(config: HiccupConfig) -> Self {
+        Self {
+            config,
+            state: HiccupState::Running,
+            failures: 0,
+            running_periods: 0,
+        }
+    }
+
+    pub fn state(&self) -> HiccupState {
+        self.state
+    }
+
+    /// Number of consecutive failures so far
+    pub fn failures(&self) -> u8 {
+        self.failures
+    }
+
+    /// Advance the state machine by one period
+    ///
+    /// `fault` should be true if a fault has occurred since the last step, or
+    /// while off, if the fault is still active.
+    pub fn step(&mut self, fault: bool) -> HiccupAction {
+        match self.state {
+            HiccupState::Running if fault => self.fail(),
+            HiccupState::Running => {
+                self.running_periods = self.running_periods.saturating_add(1);
+                if self.running_periods >= self.config.settle_periods {
+                    self.failures = 0;
+                }
+                HiccupAction::None
+            }
+            HiccupState::Off { remaining } if remaining > 1 => {
+                self.state = HiccupState::Off {
+                    remaining: remaining - 1,
+                };
+                HiccupAction::None
+            }
+            // Fault is still active, no point in trying
+            HiccupState::Off { .. } if fault => self.fail(),
+            HiccupState::Off { .. } => {
+                self.state = HiccupState::Running;
+                self.running_periods = 0;
+                HiccupAction::Enable
+            }
+            HiccupState::Latched => HiccupAction::None,
+        }
+    }
+
+    /// Leave the latched state, outputs will be re-enabled on the next step
+    /// unless the fault is still active.
+    pub fn reset(&mut self) {
+        self.failures = 0;
+        self.state = HiccupState::Off { remaining: 1 };
+    }
+
+    fn fail(&mut self) -> HiccupAction {
+        self.failures = self.failures.saturating_add(1);
+        self.state = if self.failures > self.config.max_retries {
+            HiccupState::Latched
+        } else {
+            HiccupState::Off {
+                remaining: self.config.off_periods,
+            }
+        };
+        HiccupAction::Disable
+    }
+}
+
+/// Hiccup mode controller for a fault source and the outputs configured with it
+///
+/// After a fault, the outputs are kept off for a number of periods before being
+/// re-enabled. If the fault triggers again too many times in a row, the outputs
+/// are latched off until [`Hiccup::reset`] is called.
+///
+/// ```text
+///          fault          retry           fault      retry
+///            |              |               |          |
+/// out  ------*              *---------------*          *-------------------
+///            |<-off_periods>|               |          |
+///            *--------------*               *----------*
+/// ```
+///
+/// ```
+/// let mut hiccup = Hiccup::new(
+///     hr_control.fault_3,
+///     &fault_source3,
+///     (out1, out2),
+///     HiccupConfig::default().off_periods(50).max_retries(5),
+/// );
+///
+/// // In the master timer repetition interrupt
+/// master.clear_repetition_interrupt();
+/// if hiccup.poll() == HiccupState::Latched {
+///     defmt::error!("Too many faults");
+/// }
+/// ```
+pub struct Hiccup<M, O> {
+    monitor: M,
+    outputs: O,
+    controller: HiccupController,
+}
+
+impl<M: FaultMonitor, O: HrOutputSet> Hiccup<M, O> {
+    /// Create a new hiccup controller
+    ///
+    /// `outputs` should be exactly the outputs whose timers were configured with `_source`.
+    pub fn new<FS: FaultSource<Monitor = M>>(
+        monitor: M,
+        _source: &FS,
+        outputs: O,
+        config: HiccupConfig,
+    ) -> Self {
+        Self {
+            monitor,
+            outputs,
+            controller: HiccupController::new(config),
+        }
+    }
+
+    /// Call this once every period, typically from the master timer repetition interrupt
+    pub fn poll(&mut self) -> HiccupState {
+        if self.controller.state() != HiccupState::Running {
+            // Clearing the fault does nothing while the fault is still active
+            self.monitor.clear_fault();
+        }
+
+        match self.controller.step(self.monitor.is_fault_active()) {
+            HiccupAction::None => (),
+            HiccupAction::Disable => self.outputs.disable_all(),
+            HiccupAction::Enable => {
+                self.monitor.clear_fault();
+                self.outputs.enable_all();
+            }
+        }
+
+        self.controller.state()
+    }
+
+    pub fn state(&self) -> HiccupState {
+        self.controller.state()
+    }
+
+    /// Leave the latched state, see [`HiccupController::reset`]
+    pub fn reset(&mut self) {
+        self.controller.reset();
+    }
+
+    /// Release the fault monitor and the outputs
+    pub fn free(self) -> (M, O) {
+        (self.monitor, self.outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller() -> HiccupController {
+        HiccupController::new(
+            HiccupConfig::default()
+                .off_periods(3)
+                .max_retries(2)
+                .settle_periods(5),
+        )
+    }
+
+    /// Step through the rest of the off periods, `fault` is ignored during these
+    fn wait_off(c: &mut HiccupController, fault: bool) {
+        while let HiccupState::Off { remaining } = c.state() {
+            if remaining == 1 {
+                break;
+            }
+            assert_eq!(c.step(fault), HiccupAction::None);
+        }
+    }
+
+    #[test]
+    fn fault_off_then_retry() {
+        let mut c = controller();
+        assert_eq!(c.step(false), HiccupAction::None);
+        assert_eq!(c.state(), HiccupState::Running);
+
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        assert_eq!(c.state(), HiccupState::Off { remaining: 3 });
+        assert_eq!(c.failures(), 1);
+
+        assert_eq!(c.step(false), HiccupAction::None);
+        assert_eq!(c.state(), HiccupState::Off { remaining: 2 });
+        assert_eq!(c.step(false), HiccupAction::None);
+        assert_eq!(c.state(), HiccupState::Off { remaining: 1 });
+
+        assert_eq!(c.step(false), HiccupAction::Enable);
+        assert_eq!(c.state(), HiccupState::Running);
+        assert_eq!(c.failures(), 1);
+    }
+
+    #[test]
+    fn settled_retry_resets_failures() {
+        let mut c = controller();
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        wait_off(&mut c, false);
+        assert_eq!(c.step(false), HiccupAction::Enable);
+
+        for _ in 0..4 {
+            assert_eq!(c.step(false), HiccupAction::None);
+            assert_eq!(c.failures(), 1);
+        }
+        assert_eq!(c.step(false), HiccupAction::None);
+        assert_eq!(c.failures(), 0);
+
+        // A new fault starts counting from the beginning again
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        assert_eq!(c.failures(), 1);
+    }
+
+    #[test]
+    fn failed_retries_latch() {
+        let mut c = controller();
+        assert_eq!(c.step(true), HiccupAction::Disable);
+
+        // Fault still active at the end of the off periods
+        wait_off(&mut c, true);
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        assert_eq!(c.failures(), 2);
+        assert_eq!(c.state(), HiccupState::Off { remaining: 3 });
+
+        // Retry, but the fault comes back before settling
+        wait_off(&mut c, false);
+        assert_eq!(c.step(false), HiccupAction::Enable);
+        assert_eq!(c.step(false), HiccupAction::None);
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        assert_eq!(c.failures(), 3);
+        assert_eq!(c.state(), HiccupState::Latched);
+
+        // Stays latched whatever happens
+        assert_eq!(c.step(false), HiccupAction::None);
+        assert_eq!(c.step(true), HiccupAction::None);
+        assert_eq!(c.state(), HiccupState::Latched);
+    }
+
+    #[test]
+    fn reset_with_fault_still_active() {
+        let mut c = controller();
+        for _ in 0..2 {
+            assert_eq!(c.step(true), HiccupAction::Disable);
+            wait_off(&mut c, true);
+        }
+        assert_eq!(c.state(), HiccupState::Off { remaining: 1 });
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        assert_eq!(c.state(), HiccupState::Latched);
+
+        c.reset();
+        assert_eq!(c.failures(), 0);
+
+        // Not re-enabled since the fault is still active
+        assert_eq!(c.step(true), HiccupAction::Disable);
+        assert_eq!(c.failures(), 1);
+        assert_eq!(c.state(), HiccupState::Off { remaining: 3 });
+
+        wait_off(&mut c, false);
+        assert_eq!(c.step(false), HiccupAction::Enable);
+        assert_eq!(c.state(), HiccupState::Running);
+    }
+}
@@ -32,17 +32,32 @@ compile_error!(
 );
 
 pub mod adc_trigger;
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod burst_dma;
 pub mod capture;
+pub mod center_aligned;
 pub mod compare_register;
 pub mod control;
 pub mod deadtime;
+pub mod dma;
 pub mod event;
 pub mod ext;
 pub mod external_event;
 pub mod fault;
+pub mod full_bridge;
+pub mod half_bridge;
+pub mod hiccup;
+pub mod llc;
+pub mod multi_phase;
 pub mod output;
+pub mod phase_shift;
+#[cfg(feature = "embedded-hal")]
+pub mod pwm;
+pub mod pwm_input;
 pub mod timer;
 pub mod timer_eev_cfg;
+pub mod timer_group;
 
 #[cfg(feature = "stm32f334")]
 pub use stm32f3::stm32f3x4 as pac;
@@ -344,6 +359,8 @@ pub struct HrPwmBuilder<
     interleaved_mode: InterleavedMode, // Also includes half mode
     repetition_counter: u8,
     deadtime: Option<DeadtimeConfig>,
+    deadtime_lock: bool,
+    fault_lock: bool,
     enable_repetition_interrupt: bool,
     eev_cfg: EevCfgs<TIM>,
     // TODO Add DAC triggers for stm32f334 (RM0364 21.3.19) and stm32h7 if applicable
@@ -369,6 +386,23 @@ pub struct HrParts<
     pub out1: HrOut1<TIM, PSCL, DacRst, DacStp>,
     pub out2: HrOut2<TIM, PSCL, DacRst, DacStp>,
     pub dma_channel: timer::DmaChannel<TIM>,
+    pub deadtime: deadtime::DeadtimeHandle<TIM>,
+}
+
+/// Part of the timer configuration is locked
+///
+/// The lock bits are only cleared by a system reset, they survive a software reset
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    /// The fault configuration is locked, FLTLCK
+    Faults,
+
+    /// The deadtime configuration is locked, DTxLK/DTxSLK
+    Deadtime,
+
+    /// The configuration of the fault input is locked, FLTxLCK
+    FaultInput,
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -479,7 +513,9 @@ macro_rules! hrtim_finalize_body {
             tim.fltr().modify(|_, w| w.flt6en().bit(fault_enable_bits & (1 << 5) != 0));
 
             // ... and lock configuration
-            tim.fltr().modify(|_r, w| w.fltlck().set_bit());
+            if $this.fault_lock {
+                tim.fltr().modify(|_r, w| w.fltlck().set_bit());
+            }
 
             tim.outr().modify(|_r, w| w
                 // Set actions on fault for both outputs
@@ -509,10 +545,10 @@ macro_rules! hrtim_finalize_body {
                     .sdtf().bit(deadtime_falling_sign)
 
                     // Lock configuration
-                    .dtflk().set_bit()
-                    .dtfslk().set_bit()
-                    .dtrlk().set_bit()
-                    .dtrslk().set_bit()
+                    .dtflk().bit($this.deadtime_lock)
+                    .dtfslk().bit($this.deadtime_lock)
+                    .dtrlk().bit($this.deadtime_lock)
+                    .dtrslk().bit($this.deadtime_lock)
                 );
                 tim.outr().modify(|_r, w| w.dten().set_bit());
             }
@@ -633,6 +669,8 @@ where
             preload_source,
             repetition_counter,
             deadtime,
+            deadtime_lock,
+            fault_lock,
             enable_repetition_interrupt,
             eev_cfg,
             dac_rst_trigger,
@@ -664,6 +702,8 @@ where
             preload_source,
             repetition_counter,
             deadtime,
+            deadtime_lock,
+            fault_lock,
             enable_repetition_interrupt,
             eev_cfg,
             dac_rst_trigger,
@@ -779,6 +819,8 @@ where
             preload_source,
             repetition_counter,
             deadtime,
+            deadtime_lock,
+            fault_lock,
             enable_repetition_interrupt,
             eev_cfg,
             dac_rst_trigger: _,
@@ -804,6 +846,8 @@ where
             preload_source,
             repetition_counter,
             deadtime,
+            deadtime_lock,
+            fault_lock,
             enable_repetition_interrupt,
             eev_cfg,
             dac_rst_trigger: PhantomData,
@@ -848,6 +892,8 @@ impl HrPwmAdvExt for HRTIM_MASTER {
             interleaved_mode: InterleavedMode::Disabled,
             repetition_counter: 0,
             deadtime: None,
+            deadtime_lock: true,
+            fault_lock: true,
             enable_repetition_interrupt: false,
             eev_cfg: EevCfgs::default(),
             dac_rst_trigger: PhantomData,
@@ -892,6 +938,8 @@ impl<TIM: InstanceX> HrPwmAdvExt for TIM {
             interleaved_mode: InterleavedMode::Disabled,
             repetition_counter: 0,
             deadtime: None,
+            deadtime_lock: true,
+            fault_lock: true,
             enable_repetition_interrupt: false,
             eev_cfg: EevCfgs::default(),
             dac_rst_trigger: PhantomData,
@@ -917,6 +965,15 @@ where
         self
     }
 
+    /// Lock the fault configuration of the timer after finalizing, this is the default
+    ///
+    /// Once locked, the fault configuration can not be changed until the next system reset
+    pub fn fault_lock(mut self, lock: bool) -> Self {
+        self.fault_lock = lock;
+
+        self
+    }
+
     pub fn fault_action1(mut self, fault_action1: FaultAction) -> Self {
         self.fault1_bits = fault_action1 as _;
 
@@ -989,6 +1046,16 @@ where
         self
     }
 
+    /// Lock the deadtime configuration after finalizing, this is the default
+    ///
+    /// Once locked, the deadtime can not be changed until the next system reset.
+    /// Leave unlocked to change the deadtime at runtime using [`deadtime::DeadtimeHandle`]
+    pub fn deadtime_lock(mut self, lock: bool) -> Self {
+        self.deadtime_lock = lock;
+
+        self
+    }
+
     //pub fn swap_mode(mut self, enable: bool) -> Self
 }
 
@@ -1008,10 +1075,58 @@ macro_rules! hrtim_hal {
                 // For HAL writers:
                 // Make sure to connect gpios after calling this function and then it should be safe to
                 // conjure an instance of HrParts<$TIMX, PSCL, PINS::Out<PSCL>>
+                //
+                // NOTE: Any locked configuration from before a software reset is silently kept,
+                // use `_try_init` to get an error instead
                 pub fn _init(self, _control: &mut HrPwmControl) -> (P1, P2) {
                     hrtim_finalize_body!(self, PreloadSource, $TIMX, [$($out)*]);
                     (self.pin1, self.pin2)
                 }
+
+                /// Same as `_init` but returns an error, without writing anything, if the
+                /// configuration of the timer is still locked from before a software reset
+                pub fn _try_init(self, control: &mut HrPwmControl) -> Result<(P1, P2), LockError> {
+                    let tim = unsafe { &*$TIMX::ptr() };
+
+                    if tim.fltr().read().fltlck().bit() {
+                        return Err(LockError::Faults);
+                    }
+
+                    let dtr = tim.dtr().read();
+                    let is_deadtime_locked = dtr.dtrlk().bit() || dtr.dtrslk().bit()
+                        || dtr.dtflk().bit() || dtr.dtfslk().bit();
+                    if self.deadtime.is_some() && is_deadtime_locked {
+                        return Err(LockError::Deadtime);
+                    }
+
+                    Ok(self._init(control))
+                }
+            }
+
+            impl<PSCL, P1, P2, DacRst, DacStp>
+                half_bridge::HalfBridgeBuilder<$TIMX, PSCL, PreloadSource, P1, P2, DacRst, DacStp>
+            where
+                DacRst: DacResetTrigger,
+                DacStp: DacStepTrigger,
+                PSCL: HrtimPrescaler,
+                P1: Output1Pin<$TIMX>,
+                P2: Output2Pin<$TIMX>,
+            {
+                // For HAL writers:
+                // Same as for `HrPwmBuilder::_init` but conjure an instance of
+                // HalfBridgeParts<$TIMX, PSCL, ..> instead
+                pub fn _init(self, control: &mut HrPwmControl) -> (P1, P2) {
+                    let pins = self.builder._init(control);
+                    half_bridge::wire_complementary::<$TIMX, PSCL>();
+                    pins
+                }
+
+                /// See `HrPwmBuilder::_try_init`
+                pub fn _try_init(self, control: &mut HrPwmControl) -> Result<(P1, P2), LockError> {
+                    let pins = self.builder._try_init(control)?;
+                    half_bridge::wire_complementary::<$TIMX, PSCL>();
+                    Ok(pins)
+                }
             }
         )+
     };
@@ -1075,6 +1190,13 @@ macro_rules! impl_pscl {
     )+};
 }
 
+/// f_HRCK / f_HRTIM with a prescaler of 1
+#[cfg(any(feature = "stm32f3", feature = "stm32g4"))]
+pub(crate) const HRCK_MULTIPLIER: u64 = 32;
+
+#[cfg(feature = "stm32h7")]
+pub(crate) const HRCK_MULTIPLIER: u64 = 1;
+
 #[cfg(any(feature = "stm32f3", feature = "stm32g4"))]
 pub type PsclDefault = Pscl128;
 
@@ -0,0 +1,208 @@
+use fugit::HertzU32;
+
+use crate::compare_register::{HrCompareRegister, HrCr1};
+use crate::control::HrPwmCtrl;
+use crate::ext::{MasterCrR, MasterExt, TimExt};
+use crate::pac::HRTIM_COMMON;
+use crate::timer::{HrTimer, Instance, InstanceX};
+use crate::HrtimPrescaler;
+
+/// Burst mode used at light load, see [`Llc::with_burst_mode`]
+///
+/// The burst mode controller is clocked by the period of the LLC timer, so all values
+/// are in number of switching periods.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug)]
+pub struct LlcBurst {
+    /// Enter burst mode when the requested period is below this value
+    pub threshold_period: u16,
+
+    /// Length of one burst period, idle and run, in switching periods
+    pub burst_period: u16,
+
+    /// Number of switching periods the outputs are idle at the start of each burst period
+    pub idle_periods: u16,
+}
+
+/// Variable frequency resonant (LLC) converter on one timer
+///
+/// Output 1 and 2 should be a complementary pair with deadtime, see
+/// [`crate::HrPwmBuilder::complementary`]. The duty is locked at 50% by keeping cr1 at
+/// half the period. The timer has to be configured with preload enabled, see
+/// [`crate::HrPwmBuilder::preload`].
+///
+/// NOTE: Burst mode uses the burst mode controller which is shared by all timers
+///
+/// ```
+/// let mut llc = Llc::new(timer, cr1, f_hrtim, 80.kHz(), 300.kHz())
+///     .with_burst_mode(LlcBurst { threshold_period: 2_000, burst_period: 8, idle_periods: 6 });
+///
+/// llc.set_frequency(&mut hr_control.control, 150.kHz());
+/// ```
+pub struct Llc<T: HrTimer> {
+    timer: T,
+    cr: HrCr1<T::Timer, T::Prescaler>,
+    f_tick: u64,
+    min_period: u16,
+    max_period: u16,
+    burst: Option<LlcBurst>,
+    is_bursting: bool,
+}
+
+impl<T> Llc<T>
+where
+    T: HrTimer,
+    T::Timer: InstanceX,
+    T::Prescaler: HrtimPrescaler,
+{
+    /// Take ownership of the timer and cr1 and limit the frequency to `min_frequency..=max_frequency`
+    ///
+    /// `f_hrtim` is the frequency of the HRTIM clock, f_HRTIM
+    ///
+    /// Panic if preload is not enabled for the timer or if `min_frequency > max_frequency`
+    pub fn new(
+        timer: T,
+        cr: HrCr1<T::Timer, T::Prescaler>,
+        f_hrtim: HertzU32,
+        min_frequency: HertzU32,
+        max_frequency: HertzU32,
+    ) -> Self {
+        assert!(min_frequency <= max_frequency);
+
+        let tim = unsafe { &*<T::Timer>::ptr() };
+        assert!(
+            tim.cr().read().preen().bit_is_set(),
+            "Preload has to be enabled"
+        );
+
+        let f_tick =
+            u64::from(f_hrtim.raw()) * crate::HRCK_MULTIPLIER / u64::from(<T::Prescaler>::VALUE);
+
+        let mut this = Self {
+            timer,
+            cr,
+            f_tick,
+            min_period: 0,
+            max_period: 0,
+            burst: None,
+            is_bursting: false,
+        };
+        this.min_period = this.frequency_to_period(max_frequency);
+        this.max_period = this.frequency_to_period(min_frequency);
+
+        this
+    }
+
+    /// Switch to burst mode when the requested period is below `burst.threshold_period`
+    pub fn with_burst_mode(mut self, burst: LlcBurst) -> Self {
+        assert!(burst.idle_periods < burst.burst_period);
+        self.burst = Some(burst);
+
+        self
+    }
+
+    /// Set the switching frequency, saturated to the frequency limits
+    ///
+    /// Returns the period actually written
+    pub fn set_frequency(&mut self, hr_control: &mut HrPwmCtrl, frequency: HertzU32) -> u16 {
+        let period = self.frequency_to_period(frequency);
+        self.set_period(hr_control, period)
+    }
+
+    /// Set the period and the 50% compare value, saturated to the frequency limits
+    ///
+    /// Both are transferred from the preload registers at the same update event.
+    ///
+    /// Returns the period actually written
+    pub fn set_period(&mut self, hr_control: &mut HrPwmCtrl, period: u16) -> u16 {
+        if let Some(burst) = self.burst {
+            let should_burst = period < burst.threshold_period;
+            if should_burst != self.is_bursting {
+                self.set_burst(burst, should_burst);
+            }
+        }
+
+        let period = period.clamp(self.min_period, self.max_period);
+
+        self.timer.disable_register_updates(hr_control);
+        self.timer.set_period(period);
+        self.cr.set_duty_clamped(period / 2);
+        self.timer.enable_register_updates(hr_control);
+
+        period
+    }
+
+    pub fn get_period(&self) -> u16 {
+        self.timer.get_period()
+    }
+
+    /// Current switching frequency
+    pub fn get_frequency(&self) -> HertzU32 {
+        let period = u64::from(self.get_period().max(1));
+        HertzU32::from_raw((self.f_tick / period) as u32)
+    }
+
+    /// Is the converter currently in burst mode
+    pub fn is_bursting(&self) -> bool {
+        self.is_bursting
+    }
+
+    /// Release the timer and compare register, burst mode is stopped if active
+    pub fn free(mut self) -> (T, HrCr1<T::Timer, T::Prescaler>) {
+        if let Some(burst) = self.burst {
+            if self.is_bursting {
+                self.set_burst(burst, false);
+            }
+        }
+
+        (self.timer, self.cr)
+    }
+
+    fn frequency_to_period(&self, frequency: HertzU32) -> u16 {
+        let frequency = u64::from(frequency.raw().max(1));
+        let period = (self.f_tick + frequency / 2) / frequency;
+
+        period.clamp(
+            2 * u64::from(<T::Prescaler>::MIN_CR),
+            u64::from(<T::Prescaler>::MAX_CR),
+        ) as u16
+    }
+
+    fn set_burst(&mut self, burst: LlcBurst, enable: bool) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        let tim = unsafe { &*<T::Timer>::ptr() };
+
+        if !enable {
+            common.bmcr().modify(|_r, w| w.bme().clear_bit());
+            self.is_bursting = false;
+            return;
+        }
+
+        // Both outputs go to their idle level while in the idle part of the burst
+        tim.outr()
+            .modify(|_r, w| w.idlem1().set_bit().idlem2().set_bit());
+
+        common.bmper().write(|w| w.bmper().set(burst.burst_period));
+        common.bmcmpr().write(|w| w.bmcmp().set(burst.idle_periods));
+
+        // BMCLK: Master is 0, then timer A, B... counter reset/roll-over
+        let clock = <T::Timer as InstanceX>::T_X as u8 + 1;
+
+        // SAFETY: clock is one of the timer clock sources
+        common.bmcr().modify(|_r, w| unsafe {
+            w.bmclk()
+                .bits(clock)
+                .bmom()
+                .continuous()
+                .bmpren()
+                .set_bit()
+                .bme()
+                .set_bit()
+        });
+
+        // Start the first burst immediately, then keep bursting until disabled
+        common.bmtrgr().write(|w| w.sw().set_bit());
+
+        self.is_bursting = true;
+    }
+}
@@ -0,0 +1,277 @@
+use crate::compare_register::{HrCompareRegister, HrCr1, HrCr2, HrCr3, HrCr4};
+use crate::event::TimerResetEventSource;
+use crate::ext::{MasterCrW, MasterExt, TimCrW};
+use crate::output::HrOutputSet;
+use crate::pac::HRTIM_MASTER;
+use crate::phase_shift::phase_compare_value;
+use crate::timer::{timx_perr, HrSlaveTimer, HrTimer, Instance, InstanceX};
+use crate::timer_group::AnyHrCr;
+use crate::HrtimPrescaler;
+
+/// Max number of phases, one reset on the master period and one for each master compare register
+pub const MAX_PHASES: usize = 5;
+
+type MasterCrs<PSCL> = (
+    HrCr1<HRTIM_MASTER, PSCL>,
+    HrCr2<HRTIM_MASTER, PSCL>,
+    HrCr3<HRTIM_MASTER, PSCL>,
+    HrCr4<HRTIM_MASTER, PSCL>,
+);
+
+struct PhaseSlot {
+    duty_cr: AnyHrCr,
+    output_bits: u32,
+}
+
+/// Builder for [`MultiPhase`]
+pub struct MultiPhaseBuilder<M, PSCL> {
+    master: M,
+    master_crs: MasterCrs<PSCL>,
+    period: u16,
+    phases: [Option<PhaseSlot>; MAX_PHASES],
+    len: usize,
+}
+
+impl<M, PSCL> MultiPhaseBuilder<M, PSCL>
+where
+    M: HrTimer<Timer = HRTIM_MASTER, Prescaler = PSCL>,
+    PSCL: HrtimPrescaler,
+{
+    /// Use the master timer with all of its compare registers for phase shifting
+    pub fn new(master: M, master_crs: MasterCrs<PSCL>, period: u16) -> Self {
+        Self {
+            master,
+            master_crs,
+            period,
+            phases: Default::default(),
+            len: 0,
+        }
+    }
+
+    /// Add the next phase
+    ///
+    /// The timer is reset on the master period for the first phase and on master compare
+    /// 1..4 for the rest. Its period and compare 1 are updated on master timer update.
+    /// `duty_cr` is used for the duty of the phase, `outputs` should already be configured
+    /// to be set on the timer period and reset on `duty_cr`.
+    ///
+    /// Panic if there are already [`MAX_PHASES`] phases
+    pub fn phase<S, O>(mut self, timer: &mut S, duty_cr: HrCr1<S::Timer, PSCL>, outputs: O) -> Self
+    where
+        S: HrSlaveTimer<Prescaler = PSCL>,
+        S::Timer: InstanceX,
+        M: TimerResetEventSource<S::Timer, PSCL>,
+        O: HrOutputSet,
+    {
+        assert!(self.len < MAX_PHASES);
+
+        match self.len {
+            0 => timer.enable_reset_event(&self.master),
+            1 => timer.enable_reset_event(&self.master_crs.0),
+            2 => timer.enable_reset_event(&self.master_crs.1),
+            3 => timer.enable_reset_event(&self.master_crs.2),
+            _ => timer.enable_reset_event(&self.master_crs.3),
+        }
+
+        let tim = unsafe { &*<S::Timer>::ptr() };
+
+        // Period and compare 1 are preloaded and transferred on master timer update
+        tim.cr()
+            .modify(|_r, w| w.preen().set_bit().mstu().set_bit());
+        timer.set_period(self.period);
+
+        self.phases[self.len] = Some(PhaseSlot {
+            duty_cr: duty_cr.into(),
+            output_bits: outputs.output_bits(),
+        });
+        self.len += 1;
+
+        self
+    }
+
+    pub fn finalize(self) -> MultiPhase<M, PSCL> {
+        let master = unsafe { &*HRTIM_MASTER::ptr() };
+
+        // SAFETY: We hold the master timer
+        master
+            .cr()
+            .modify(|_r, w| w.mrepu().set_bit().preen().set_bit());
+
+        let mut multi_phase = MultiPhase {
+            master: self.master,
+            master_crs: self.master_crs,
+            period: self.period,
+            phases: self.phases,
+            len: self.len,
+            active: (1 << self.len) - 1,
+        };
+        multi_phase.set_period(self.period);
+
+        multi_phase
+    }
+}
+
+/// N-phase interleaved converter across timer A..F
+///
+/// Each phase runs on its own timer, phase shifted by 360°/N from each other using
+/// the master timer. Period and duty updates of all the phases are transferred at
+/// the same time on the master repetition update.
+///
+/// ```
+/// let mut multi_phase = MultiPhaseBuilder::new(mtimer, (mcr1, mcr2, mcr3, mcr4), 20_000)
+///     .phase(&mut timer_a, cr1_a, out_a)
+///     .phase(&mut timer_b, cr1_b, out_b)
+///     .phase(&mut timer_c, cr1_c, out_c)
+///     .finalize();
+///
+/// multi_phase.set_duty(5_000);
+///
+/// // Light load, phase shedding, the remaining phases are now 180° apart
+/// multi_phase.disable_phase(2);
+/// ```
+pub struct MultiPhase<M, PSCL> {
+    master: M,
+    master_crs: MasterCrs<PSCL>,
+    period: u16,
+    phases: [Option<PhaseSlot>; MAX_PHASES],
+    len: usize,
+
+    /// Bit n is set if phase n is active
+    active: u8,
+}
+
+impl<M, PSCL> MultiPhase<M, PSCL>
+where
+    M: HrTimer<Timer = HRTIM_MASTER, Prescaler = PSCL>,
+    PSCL: HrtimPrescaler,
+{
+    /// Number of phases, both active and inactive
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of active phases
+    pub fn active_phases(&self) -> usize {
+        self.active.count_ones() as usize
+    }
+
+    pub fn is_phase_active(&self, phase: usize) -> bool {
+        self.active & (1 << phase) != 0
+    }
+
+    /// Set the period of the master and all the phases
+    ///
+    /// NOTE: The duty is not changed, call [`Self::set_duty`] as well if needed
+    pub fn set_period(&mut self, period: u16) {
+        self.period = period;
+        self.master.set_period(period);
+
+        for slot in self.phases.iter().flatten() {
+            // SAFETY: MultiPhase owns the period of the phase timers
+            unsafe { timx_perr(slot.duty_cr.timer()) }.write(|w| unsafe { w.per().bits(period) });
+        }
+
+        self.distribute_phases();
+    }
+
+    pub fn get_period(&self) -> u16 {
+        self.period
+    }
+
+    /// Set the duty of all phases, saturated to the allowed range
+    ///
+    /// Returns the value actually written
+    pub fn set_duty(&mut self, duty: u16) -> u16 {
+        // 0 is allowed for compare 1
+        let duty = if duty == 0 {
+            0
+        } else {
+            duty.clamp(PSCL::MIN_CR, PSCL::MAX_CR)
+        };
+
+        for slot in self.phases.iter_mut().flatten() {
+            slot.duty_cr.set_duty(duty);
+        }
+
+        duty
+    }
+
+    /// Enable the outputs of the phase and redistribute the phase shifts
+    ///
+    /// Panic if `phase` is not a valid phase
+    pub fn enable_phase(&mut self, phase: usize) {
+        let output_bits = self.output_bits(phase);
+
+        self.active |= 1 << phase;
+        self.distribute_phases();
+
+        let common = unsafe { &*crate::pac::HRTIM_COMMON::ptr() };
+        common.oenr().write(|w| unsafe { w.bits(output_bits) });
+    }
+
+    /// Disable the outputs of the phase and redistribute the phase shifts
+    ///
+    /// Panic if `phase` is not a valid phase
+    pub fn disable_phase(&mut self, phase: usize) {
+        let output_bits = self.output_bits(phase);
+
+        let common = unsafe { &*crate::pac::HRTIM_COMMON::ptr() };
+        common.odisr().write(|w| unsafe { w.bits(output_bits) });
+
+        self.active &= !(1 << phase);
+        self.distribute_phases();
+    }
+
+    /// Phase shift of the phase in master ticks, `None` if the phase is not active
+    pub fn phase_shift(&self, phase: usize) -> Option<u16> {
+        if !self.is_phase_active(phase) {
+            return None;
+        }
+
+        let rank = (self.active & ((1 << phase) - 1)).count_ones();
+        Some(shift(rank, self.active_phases() as u32, self.period))
+    }
+
+    /// Release the master timer and its compare registers
+    ///
+    /// NOTE: The timers are still reset by the master timer
+    pub fn free(self) -> (M, MasterCrs<PSCL>) {
+        (self.master, self.master_crs)
+    }
+
+    /// Panic if `phase` is not a valid phase
+    fn output_bits(&self, phase: usize) -> u32 {
+        self.phases[phase]
+            .as_ref()
+            .expect("Invalid phase")
+            .output_bits
+    }
+
+    /// Spread the active phases evenly over the period
+    ///
+    /// The first phase is always reset on the master period, so it is always at 0°.
+    /// The rest get the master compare value for their rank among the active phases.
+    fn distribute_phases(&mut self) {
+        for phase in 1..self.len {
+            let Some(ticks) = self.phase_shift(phase) else {
+                continue;
+            };
+
+            let value = phase_compare_value::<PSCL>(ticks, self.period);
+            match phase {
+                1 => self.master_crs.0.set_duty(value),
+                2 => self.master_crs.1.set_duty(value),
+                3 => self.master_crs.2.set_duty(value),
+                _ => self.master_crs.3.set_duty(value),
+            }
+        }
+    }
+}
+
+fn shift(rank: u32, count: u32, period: u16) -> u16 {
+    (rank * u32::from(period) / count) as u16
+}
@@ -110,6 +110,83 @@ pub trait HrOutput<TIM, PSCL> {
     fn get_state(&self) -> State;
 }
 
+/// A set of outputs that can be enabled and disabled at the exact same time
+///
+/// Implemented for single outputs as well as tuples and arrays of outputs
+///
+/// ```
+/// (&mut out1, &mut out2).enable_all();
+/// ```
+pub trait HrOutputSet {
+    /// Bit mask of the outputs in this set, using the bit layout of the OENR/ODISR registers
+    fn output_bits(&self) -> u32;
+
+    /// Enable all outputs in this set
+    fn enable_all(&mut self) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+        // SAFETY: We own all outputs in the set, OENR is write-1-to-set so other outputs are unaffected
+        common
+            .oenr()
+            .write(|w| unsafe { w.bits(self.output_bits()) });
+    }
+
+    /// Disable all outputs in this set
+    fn disable_all(&mut self) {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+
+        // SAFETY: We own all outputs in the set, ODISR is write-1-to-set so other outputs are unaffected
+        common
+            .odisr()
+            .write(|w| unsafe { w.bits(self.output_bits()) });
+    }
+
+    /// Returns true if all outputs in this set are in the [`State::Running`] state
+    fn is_running(&self) -> bool {
+        let common = unsafe { &*HRTIM_COMMON::ptr() };
+        let bits = self.output_bits();
+
+        common.oenr().read().bits() & bits == bits
+    }
+}
+
+impl<TIM: InstanceX, PSCL, CH: ChExt, R: DacResetTrigger, S: DacStepTrigger> HrOutputSet
+    for HrOut<TIM, PSCL, CH, R, S>
+{
+    fn output_bits(&self) -> u32 {
+        1 << (TIM::T_X as u32 * 2 + CH::CH as u32) // TA1OEN is at bit 0, TA2OEN at bit 1, TB1OEN at bit 2 etc
+    }
+}
+
+impl<T: HrOutputSet> HrOutputSet for &mut T {
+    fn output_bits(&self) -> u32 {
+        (**self).output_bits()
+    }
+}
+
+impl<T: HrOutputSet, const N: usize> HrOutputSet for [T; N] {
+    fn output_bits(&self) -> u32 {
+        self.iter().fold(0, |bits, out| bits | out.output_bits())
+    }
+}
+
+macro_rules! impl_output_set_tuple {
+    ($($T:ident: $i:tt),+) => {
+        impl<$($T: HrOutputSet),+> HrOutputSet for ($($T,)+) {
+            fn output_bits(&self) -> u32 {
+                0 $(| self.$i.output_bits())+
+            }
+        }
+    };
+}
+
+impl_output_set_tuple!(A: 0);
+impl_output_set_tuple!(A: 0, B: 1);
+impl_output_set_tuple!(A: 0, B: 1, C: 2);
+impl_output_set_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_output_set_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_output_set_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum State {
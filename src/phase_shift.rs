@@ -0,0 +1,132 @@
+use crate::compare_register::{CmpExt, HrCompareRegister, HrCr};
+use crate::event::TimerResetEventSource;
+use crate::pac::HRTIM_MASTER;
+use crate::timer::{HrSlaveTimer, HrTimer};
+use crate::HrtimPrescaler;
+
+/// Phase shift relative to the master timer
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Phase {
+    /// Phase in degrees of the master period, any value is wrapped to 0..360
+    Degrees(f32),
+
+    /// Phase in master timer ticks, wrapped to 0..period
+    Ticks(u16),
+}
+
+impl Phase {
+    /// Phase in ticks of a timer with the specified period, wrapped to 0..period
+    pub fn to_ticks(self, period: u16) -> u16 {
+        let period = period.max(1);
+        match self {
+            Phase::Degrees(degrees) => {
+                let degrees = degrees % 360.0;
+                let degrees = if degrees < 0.0 {
+                    degrees + 360.0
+                } else {
+                    degrees
+                };
+                let ticks = (degrees * f32::from(period) / 360.0 + 0.5) as u32;
+                (ticks % u32::from(period)) as u16
+            }
+            Phase::Ticks(ticks) => ticks % period,
+        }
+    }
+}
+
+/// Master compare value which resets a slave `ticks` after the master period event
+///
+/// 0 ticks, same as the full period, uses the period itself since a compare value
+/// of 0 is not allowed for all compare registers
+pub(crate) fn phase_compare_value<PSCL: HrtimPrescaler>(ticks: u16, period: u16) -> u16 {
+    let value = if ticks == 0 { period } else { ticks };
+    value.clamp(PSCL::MIN_CR, PSCL::MAX_CR)
+}
+
+/// Phase shift of a slave timer relative to the master timer
+///
+/// The slave timer is reset on a compare match of one of the master's compare registers.
+///
+/// ```text
+/// master  |/      |/      |/      |/
+///   phase -->|
+/// slave   /  |/      |/      |/      |/
+/// ```
+///
+/// ```
+/// let mut phase_shift = PhaseShift::new(&mut mtimer, &mut timer_b, mcr2, Phase::Degrees(90.0));
+/// ...
+/// phase_shift.set_phase(Phase::Degrees(120.0));
+/// ```
+pub struct PhaseShift<PSCL, CMP> {
+    cr: HrCr<HRTIM_MASTER, PSCL, CMP>,
+}
+
+impl<PSCL: HrtimPrescaler, CMP: CmpExt> PhaseShift<PSCL, CMP> {
+    /// Reset `slave` on compare match with `cr` and set the initial phase
+    ///
+    /// This enables preload for the master timer, updates are transferred on the master
+    /// repetition update
+    pub fn new<M, S>(
+        _master: &mut M,
+        slave: &mut S,
+        cr: HrCr<HRTIM_MASTER, PSCL, CMP>,
+        phase: Phase,
+    ) -> Self
+    where
+        M: HrTimer<Timer = HRTIM_MASTER, Prescaler = PSCL>,
+        S: HrSlaveTimer<Prescaler = PSCL>,
+        HrCr<HRTIM_MASTER, PSCL, CMP>: TimerResetEventSource<S::Timer, PSCL>,
+    {
+        // SAFETY: We hold the master timer
+        let master = unsafe { &*HRTIM_MASTER::ptr() };
+        master
+            .cr()
+            .modify(|_r, w| w.mrepu().set_bit().preen().set_bit());
+
+        slave.enable_reset_event(&cr);
+
+        let mut this = Self { cr };
+        this.set_phase(phase);
+        this
+    }
+
+    /// Set the phase, takes effect on the next master repetition update
+    ///
+    /// Returns the phase in ticks
+    pub fn set_phase(&mut self, phase: Phase) -> u16 {
+        let period = master_period();
+        let ticks = phase.to_ticks(period);
+
+        self.cr.set_duty(phase_compare_value::<PSCL>(ticks, period));
+        ticks
+    }
+
+    /// Current phase in ticks
+    ///
+    /// NOTE: This may be slightly off from the requested phase for phases
+    /// below MIN_CR or above MAX_CR ticks, see [`HrtimPrescaler`]
+    pub fn phase_ticks(&self) -> u16 {
+        let period = master_period();
+        let value = self.cr.get_duty();
+
+        if value >= period {
+            0
+        } else {
+            value
+        }
+    }
+
+    /// Release the master compare register
+    ///
+    /// NOTE: The slave timer is still reset on compare match
+    pub fn free(self) -> HrCr<HRTIM_MASTER, PSCL, CMP> {
+        self.cr
+    }
+}
+
+pub(crate) fn master_period() -> u16 {
+    let master = unsafe { &*HRTIM_MASTER::ptr() };
+    master.perr().read().per().bits()
+}
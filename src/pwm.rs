@@ -0,0 +1,125 @@
+use core::convert::Infallible;
+
+use crate::compare_register::{CmpExt, HrCompareRegister, HrCr};
+use crate::event::EventSource;
+use crate::output::{HrOut, HrOutput};
+use crate::timer::{ChExt, HrTimer, InstanceX};
+use crate::{DacResetTrigger, DacStepTrigger, HrtimPrescaler, NoDacTrigger};
+
+/// Adapter implementing [`embedded_hal::pwm::SetDutyCycle`] for one HRTIM output
+///
+/// The output is set on the period event of `timer` and reset on the compare
+/// match of `cr`. The maximum duty cycle is the period of the timer.
+///
+/// ```
+/// let mut channel = HrPwmChannel::new(&timer, cr1, out1);
+/// channel.enable();
+/// channel.set_duty_cycle_percent(25).unwrap();
+/// ```
+pub struct HrPwmChannel<
+    'a,
+    T,
+    TIM,
+    PSCL,
+    CMP,
+    CH,
+    DacRst: DacResetTrigger = NoDacTrigger,
+    DacStp: DacStepTrigger = NoDacTrigger,
+> {
+    timer: &'a T,
+    cr: HrCr<TIM, PSCL, CMP>,
+    out: HrOut<TIM, PSCL, CH, DacRst, DacStp>,
+}
+
+impl<'a, T, TIM, PSCL, CMP, CH, DacRst, DacStp>
+    HrPwmChannel<'a, T, TIM, PSCL, CMP, CH, DacRst, DacStp>
+where
+    T: HrTimer + EventSource<TIM, PSCL>,
+    TIM: InstanceX,
+    PSCL: HrtimPrescaler,
+    CMP: CmpExt,
+    CH: ChExt,
+    DacRst: DacResetTrigger,
+    DacStp: DacStepTrigger,
+{
+    /// Set `out` on the period event of `timer` and reset it on the compare match of `cr`
+    pub fn new(
+        timer: &'a T,
+        cr: HrCr<TIM, PSCL, CMP>,
+        mut out: HrOut<TIM, PSCL, CH, DacRst, DacStp>,
+    ) -> Self {
+        out.enable_set_event(timer);
+        out.enable_rst_event(&cr);
+
+        Self { timer, cr, out }
+    }
+
+    pub fn enable(&mut self) {
+        self.out.enable();
+    }
+
+    pub fn disable(&mut self) {
+        self.out.disable();
+    }
+
+    /// Release the compare register and the output
+    ///
+    /// NOTE: The output keeps its set/reset events
+    #[allow(clippy::type_complexity)]
+    pub fn free(self) -> (HrCr<TIM, PSCL, CMP>, HrOut<TIM, PSCL, CH, DacRst, DacStp>) {
+        (self.cr, self.out)
+    }
+}
+
+impl<T, TIM, PSCL, CMP, CH, DacRst, DacStp> embedded_hal::pwm::ErrorType
+    for HrPwmChannel<'_, T, TIM, PSCL, CMP, CH, DacRst, DacStp>
+where
+    DacRst: DacResetTrigger,
+    DacStp: DacStepTrigger,
+{
+    type Error = Infallible;
+}
+
+impl<T, TIM, PSCL, CMP, CH, DacRst, DacStp> embedded_hal::pwm::SetDutyCycle
+    for HrPwmChannel<'_, T, TIM, PSCL, CMP, CH, DacRst, DacStp>
+where
+    T: HrTimer + EventSource<TIM, PSCL>,
+    TIM: InstanceX,
+    PSCL: HrtimPrescaler,
+    CMP: CmpExt,
+    CH: ChExt,
+    DacRst: DacResetTrigger,
+    DacStp: DacStepTrigger,
+{
+    fn max_duty_cycle(&self) -> u16 {
+        self.timer.get_period()
+    }
+
+    /// Set the compare value to `duty`, clamped to the range allowed by the prescaler
+    ///
+    /// A compare value of 0 or at the period would still give a short pulse, so for 0 the
+    /// set event is disabled to hold the output off, and for [`Self::max_duty_cycle`] or above
+    /// the reset event is disabled to hold the output on.
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        let (set, reset) = match duty {
+            0 => (false, true),
+            d if d >= self.max_duty_cycle() => (true, false),
+            _ => (true, true),
+        };
+
+        if set {
+            self.out.enable_set_event(self.timer);
+        } else {
+            self.out.disable_set_event(self.timer);
+        }
+
+        if reset {
+            self.out.enable_rst_event(&self.cr);
+        } else {
+            self.out.disable_rst_event(&self.cr);
+        }
+
+        self.cr.set_duty_clamped(duty);
+        Ok(())
+    }
+}
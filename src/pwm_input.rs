@@ -0,0 +1,260 @@
+use fugit::{HertzU32, HertzU64, NanosDurationU32};
+
+use crate::capture::CaptureEvent;
+use crate::control::HrPwmCtrl;
+use crate::event::TimerResetEventSource;
+use crate::ext::{Chan, MasterCrW, MasterExt, TimExt};
+use crate::timer::{Ch1, Ch2, HrSlaveTimer, HrTimer, InstanceX, TimerSplitCapture};
+use crate::{DacResetTrigger, HrtimPrescaler};
+
+#[cfg(any(feature = "stm32f3", feature = "stm32g4"))]
+use crate::{Pscl1, Pscl128, Pscl16, Pscl2, Pscl32, Pscl4, Pscl64, Pscl8};
+
+#[cfg(feature = "stm32h7")]
+use crate::{Pscl1, Pscl2, Pscl4};
+
+/// CKPSC bits, prescaler value and max period of the available prescalers, finest first
+#[cfg(any(feature = "stm32f3", feature = "stm32g4"))]
+const PRESCALERS: [(u8, u8, u16); 8] = [
+    pscl::<Pscl1>(),
+    pscl::<Pscl2>(),
+    pscl::<Pscl4>(),
+    pscl::<Pscl8>(),
+    pscl::<Pscl16>(),
+    pscl::<Pscl32>(),
+    pscl::<Pscl64>(),
+    pscl::<Pscl128>(),
+];
+
+#[cfg(feature = "stm32h7")]
+const PRESCALERS: [(u8, u8, u16); 3] = [pscl::<Pscl1>(), pscl::<Pscl2>(), pscl::<Pscl4>()];
+
+const fn pscl<P: HrtimPrescaler>() -> (u8, u8, u16) {
+    (P::BITS, P::VALUE, P::MAX_CR)
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PwmInputError {
+    /// No rising edge within the longest measurable period, the input is either
+    /// slower than the minimum frequency or not toggling at all
+    Overflow,
+
+    /// A rising edge was captured without a falling edge since the previous rising edge,
+    /// so the high time of the period is unknown. The falling edge was either missed or
+    /// is too close to the rising edge to be captured.
+    NoFallingEdge,
+}
+
+/// One period of the measured signal, see [`PwmInput::get`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PwmMeasurement {
+    /// Period in timer ticks
+    pub period_ticks: u16,
+
+    /// Time from rising to falling edge in timer ticks
+    pub high_ticks: u16,
+
+    /// Frequency of the timer ticks
+    ///
+    /// NOTE: This does not fit in 32 bits with the finest prescalers of some devices
+    pub f_tick: HertzU64,
+}
+
+impl PwmMeasurement {
+    /// Frequency of the measured signal, saturated to `u32::MAX` Hz
+    pub fn frequency(&self) -> HertzU32 {
+        let frequency = self.f_tick.raw() / u64::from(self.period_ticks.max(1));
+        HertzU32::from_raw(frequency.min(u64::from(u32::MAX)) as u32)
+    }
+
+    /// Period of the measured signal, saturated to `u32::MAX` ns
+    pub fn period(&self) -> NanosDurationU32 {
+        let nanos = u64::from(self.period_ticks) * 1_000_000_000 / self.f_tick.raw().max(1);
+        NanosDurationU32::from_ticks(nanos.min(u64::from(u32::MAX)) as u32)
+    }
+
+    /// Duty cycle in the range 0.0..=1.0
+    pub fn duty(&self) -> f32 {
+        f32::from(self.high_ticks) / f32::from(self.period_ticks.max(1))
+    }
+}
+
+/// Frequency and duty measurement of an external PWM signal
+///
+/// The timer is reset on the rising edge. Capture 1 captures the rising edge, which is the
+/// period, and capture 2 captures the falling edge, which is the high time. This needs two
+/// external events, one for each edge, connected to the same signal.
+///
+/// The finest prescaler able to measure `min_frequency` is selected automatically and the
+/// timer runs in [`crate::HrTimerMode::SingleShotRetriggerable`] mode, so it stops instead of
+/// rolling over if no rising edge arrives in time.
+///
+/// ```
+/// let rising = eev_inputs.eev_input6.bind(pin).edge_or_polarity(EdgeOrPolarity::Edge(Edge::Rising)).finalize(&mut hr_control);
+/// let falling = eev_inputs.eev_input7.bind(pin_copy).edge_or_polarity(EdgeOrPolarity::Edge(Edge::Falling)).finalize(&mut hr_control);
+/// ...
+/// let mut pwm_input = PwmInput::new(timer.split_capture(), &rising, &falling, 1.kHz(), f_hrtim, &mut hr_control.control);
+///
+/// match pwm_input.get() {
+///     Some(Ok(measurement)) => defmt::info!("{} Hz, {}", measurement.frequency().raw(), measurement.duty()),
+///     Some(Err(PwmInputError::Overflow)) => defmt::info!("No signal"),
+///     Some(Err(PwmInputError::NoFallingEdge)) => defmt::info!("Missed falling edge"),
+///     None => {}
+/// }
+/// ```
+pub struct PwmInput<TIM, PSCL, DacRst: DacResetTrigger> {
+    /// The handles are typed with `PSCL`, which is not the prescaler in use while measuring.
+    /// They are only kept to be given back by [`Self::free`] once `PSCL` has been restored,
+    /// the registers are accessed directly in the meantime.
+    split: TimerSplitCapture<TIM, PSCL, Ch1, Ch2, DacRst>,
+    f_tick: HertzU64,
+    max_period: u16,
+
+    /// A rising edge has been captured, so the next capture covers a full period
+    is_started: bool,
+}
+
+impl<TIM: InstanceX, PSCL: HrtimPrescaler, DacRst: DacResetTrigger> PwmInput<TIM, PSCL, DacRst> {
+    /// Configure the timer and captures and start the timer
+    ///
+    /// `f_hrtim` is the frequency of the HRTIM clock, f_HRTIM
+    ///
+    /// NOTE: Other handles of the timer, like its compare registers, are still typed with
+    /// `PSCL` and should not be used until the prescaler is restored by [`Self::free`]
+    ///
+    /// Panic if `min_frequency` is too low to be measured even with the coarsest prescaler
+    pub fn new<R, F>(
+        mut split: TimerSplitCapture<TIM, PSCL, Ch1, Ch2, DacRst>,
+        rising: &R,
+        falling: &F,
+        min_frequency: HertzU32,
+        f_hrtim: HertzU32,
+        hr_control: &mut HrPwmCtrl,
+    ) -> Self
+    where
+        R: CaptureEvent<TIM, PSCL> + TimerResetEventSource<TIM, PSCL>,
+        F: CaptureEvent<TIM, PSCL>,
+    {
+        let f_hrck = u64::from(f_hrtim.raw()) * crate::HRCK_MULTIPLIER;
+        let min_frequency = u64::from(min_frequency.raw().max(1));
+        let (bits, value, max_period) = PRESCALERS
+            .iter()
+            .copied()
+            .find(|&(_, value, max_period)| {
+                (f_hrck / u64::from(value)).div_ceil(min_frequency) <= u64::from(max_period)
+            })
+            .expect("min_frequency too low");
+
+        // Everything done through the typed handles is done before the prescaler is changed
+        split.timer.stop_and_reset(hr_control);
+        split.timer.set_period(max_period);
+        split.timer.enable_reset_event(rising);
+        split.ch1.add_event(rising);
+        split.ch2.add_event(falling);
+
+        let tim = unsafe { &*TIM::ptr() };
+
+        // SAFETY: We own the timer and bits is a valid prescaler
+        tim.cr()
+            .modify(|_r, w| unsafe { w.ckpsc().bits(bits).cont().clear_bit().retrig().set_bit() });
+
+        // Drop any captures from before the timer was configured
+        tim.icr().write(|w| w.cptc(0).clear().cptc(1).clear());
+
+        // Starting the timer does not depend on the prescaler
+        split.timer.start(hr_control);
+
+        Self {
+            split,
+            f_tick: HertzU64::from_raw(f_hrck / u64::from(value)),
+            max_period,
+            is_started: false,
+        }
+    }
+
+    /// Try to get the measurement of the last full period
+    ///
+    /// Returns none if no new period has been captured since last time. The first rising edge
+    /// after [`Self::new`] only starts the first period and is not reported.
+    ///
+    /// A constant 0% or 100% input has no edges at all and is reported as
+    /// [`PwmInputError::Overflow`].
+    pub fn get(&mut self) -> Option<Result<PwmMeasurement, PwmInputError>> {
+        let Some(period_ticks) = take_capture::<TIM>(Chan::Ch1) else {
+            return self.is_overflow().then_some(Err(PwmInputError::Overflow));
+        };
+
+        // The falling edge of the period just captured, anything older is stale
+        let high_ticks = take_capture::<TIM>(Chan::Ch2);
+
+        // The counter ran from when the timer was started, not from a rising edge
+        if !self.is_started {
+            self.is_started = true;
+            return None;
+        }
+
+        if period_ticks >= self.max_period {
+            return Some(Err(PwmInputError::Overflow));
+        }
+
+        let Some(high_ticks) = high_ticks else {
+            return Some(Err(PwmInputError::NoFallingEdge));
+        };
+
+        Some(Ok(PwmMeasurement {
+            period_ticks,
+            high_ticks: high_ticks.min(period_ticks),
+            f_tick: self.f_tick,
+        }))
+    }
+
+    /// The counter has stopped since there was no rising edge within the longest measurable period
+    pub fn is_overflow(&self) -> bool {
+        let tim = unsafe { &*TIM::ptr() };
+
+        // No need for exclusive access since this is only a read
+        tim.cntr().read().cnt().bits() >= self.max_period
+    }
+
+    /// Frequency of the timer ticks with the automatically selected prescaler
+    pub fn tick_frequency(&self) -> HertzU64 {
+        self.f_tick
+    }
+
+    /// Stop the timer and release it together with its capture channels
+    ///
+    /// NOTE: The reset and capture events are kept and the prescaler is restored to `PSCL`
+    pub fn free(
+        mut self,
+        hr_control: &mut HrPwmCtrl,
+    ) -> TimerSplitCapture<TIM, PSCL, Ch1, Ch2, DacRst> {
+        // Stopping the timer does not depend on the prescaler
+        self.split.timer.stop(hr_control);
+
+        let tim = unsafe { &*TIM::ptr() };
+
+        // SAFETY: PSCL::BITS is a valid prescaler
+        tim.cr()
+            .modify(|_r, w| unsafe { w.ckpsc().bits(PSCL::BITS) });
+
+        self.split
+    }
+}
+
+/// Read and clear the capture of channel `ch`, if any
+fn take_capture<TIM: InstanceX>(ch: Chan) -> Option<u16> {
+    let tim = unsafe { &*TIM::ptr() };
+
+    // No need for exclusive access since these are read only and write only registers,
+    // and we own the timer
+    if !tim.isr().read().cpt(ch as _).bit() {
+        return None;
+    }
+
+    let value = tim.cptr(ch).read().cpt().bits();
+    tim.icr().write(|w| w.cptc(ch as _).clear());
+
+    Some(value)
+}
@@ -2,7 +2,7 @@
 use crate::pac::HRTIM_TIMF;
 use crate::{
     pac::{HRTIM_MASTER, HRTIM_TIMA, HRTIM_TIMB, HRTIM_TIMC, HRTIM_TIMD, HRTIM_TIME},
-    DacResetTrigger, NoDacTrigger,
+    DacResetTrigger, HrCountingDirection, NoDacTrigger,
 };
 use core::{marker::PhantomData, ops::Deref};
 
@@ -17,7 +17,7 @@ pub enum SwapPins {
 use super::{
     capture::{self, HrCapt, HrCapture},
     control::HrPwmCtrl,
-    ext::{MasterDierW, MasterExt, MasterIcr, TimExt},
+    ext::{MasterExt, MasterIcr, TimExt},
     HrtimPrescaler,
 };
 
@@ -116,6 +116,167 @@ hrtim_timer! {
 #[cfg(feature = "hrtim_v2")]
 hrtim_timer! {HRTIM_TIMF: hrtim_timf, F,}
 
+/// Interrupt sources of a HRTIM timer
+///
+/// Not all interrupts are available for all timers, see [`HrTimerInterrupt::is_available`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HrTimerInterrupt {
+    /// Compare match
+    Compare(Cmp),
+
+    /// Repetition, the counter rolled over or was reset while the repetition counter was 0
+    Repetition,
+
+    /// Register update, preloaded registers were transferred to the active registers
+    Update,
+
+    /// Synchronization input
+    ///
+    /// NOTE: Only available for the master timer
+    Sync,
+
+    /// Capture
+    ///
+    /// NOTE: Not available for the master timer
+    Capture(Chan),
+
+    /// Output set event
+    ///
+    /// NOTE: Not available for the master timer
+    OutputSet(Chan),
+
+    /// Output reset event
+    ///
+    /// NOTE: Not available for the master timer
+    OutputReset(Chan),
+
+    /// Counter reset or roll-over
+    ///
+    /// NOTE: Not available for the master timer
+    CounterReset,
+
+    /// Delayed protection
+    ///
+    /// NOTE: Not available for the master timer
+    DelayedProtection,
+}
+
+impl HrTimerInterrupt {
+    /// Returns true if this interrupt is available for `timer`
+    pub const fn is_available(self, timer: Timer) -> bool {
+        match self {
+            Self::Compare(_) | Self::Repetition | Self::Update => true,
+            Self::Sync => matches!(timer, Timer::Master),
+            Self::Capture(_)
+            | Self::OutputSet(_)
+            | Self::OutputReset(_)
+            | Self::CounterReset
+            | Self::DelayedProtection => matches!(timer, Timer::Tim(_)),
+        }
+    }
+
+    /// Bit in the DIER, ISR and ICR registers
+    pub(crate) const fn bits(self) -> u32 {
+        match self {
+            Self::Compare(cmp) => 1 << (cmp as u8),
+            Self::Repetition => 1 << 4,
+            Self::Sync => 1 << 5,
+            Self::Update => 1 << 6,
+            Self::Capture(ch) => 1 << (ch as u8 + 7),
+            Self::OutputSet(ch) => 1 << (ch as u8 * 2 + 9),
+            Self::OutputReset(ch) => 1 << (ch as u8 * 2 + 10),
+            Self::CounterReset => 1 << 13,
+            Self::DelayedProtection => 1 << 14,
+        }
+    }
+
+    const fn all_bits(timer: Timer) -> u32 {
+        match timer {
+            Timer::Master => 0b111_1111,
+            Timer::Tim(_) => 0b111_1111_1101_1111,
+        }
+    }
+}
+
+/// Read-modify-write the DIER register of `TIM`
+///
+/// DIER is shared between the timer and the parts split off from it, like the capture channels
+/// and dma requests, which may live in different interrupt contexts. The update is done in a
+/// critical section so that no concurrent modification is lost.
+pub(crate) fn modify_dier<TIM: Instance>(f: impl FnOnce(u32) -> u32) {
+    let tim = unsafe { &*TIM::ptr() };
+
+    critical_section::with(|_| unsafe {
+        tim.dier().modify(|r, w| w.bits(f(r.bits())));
+    });
+}
+
+/// Set of pending timer interrupts, see [`HrTimer::pending_set`]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HrTimerInterruptSet(u32);
+
+impl HrTimerInterruptSet {
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, interrupt: HrTimerInterrupt) -> bool {
+        self.0 & interrupt.bits() != 0
+    }
+}
+
+/// A set of timers whose register updates can be disabled and enabled at the exact same time
+///
+/// Implemented for single timers as well as tuples and arrays of timers,
+/// see [`HrPwmCtrl::update_transaction`]
+pub trait HrTimerSet {
+    /// Bit mask of the timers in this set, using the bit layout of the TxUDIS bits in CR1
+    fn udis_bits(&self) -> u32;
+}
+
+impl<TIM: Instance, PSCL, CPT1, CPT2, DacRst: DacResetTrigger> HrTimerSet
+    for HrTim<TIM, PSCL, CPT1, CPT2, DacRst>
+{
+    fn udis_bits(&self) -> u32 {
+        match TIM::TIMX {
+            Timer::Master => 1,                   // MUDIS is at bit 0
+            Timer::Tim(v) => 1 << (v as u32 + 1), // TAUDIS is at bit 1, TBUDIS at bit 2 etc
+        }
+    }
+}
+
+impl<T: HrTimerSet> HrTimerSet for &mut T {
+    fn udis_bits(&self) -> u32 {
+        (**self).udis_bits()
+    }
+}
+
+impl<T: HrTimerSet, const N: usize> HrTimerSet for [T; N] {
+    fn udis_bits(&self) -> u32 {
+        self.iter().fold(0, |bits, timer| bits | timer.udis_bits())
+    }
+}
+
+macro_rules! impl_timer_set_tuple {
+    ($($T:ident: $i:tt),+) => {
+        impl<$($T: HrTimerSet),+> HrTimerSet for ($($T,)+) {
+            fn udis_bits(&self) -> u32 {
+                0 $(| self.$i.udis_bits())+
+            }
+        }
+    };
+}
+
+impl_timer_set_tuple!(A: 0);
+impl_timer_set_tuple!(A: 0, B: 1);
+impl_timer_set_tuple!(A: 0, B: 1, C: 2);
+impl_timer_set_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_timer_set_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_timer_set_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_timer_set_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+
 /// This is the DMA channel of a HRTIM timer
 ///
 /// Every HRTIM timer including the master timer has a DMA channel
@@ -123,6 +284,14 @@ pub struct DmaChannel<TIM> {
     _x: PhantomData<TIM>,
 }
 
+impl<TIM> DmaChannel<TIM> {
+    /// # Safety
+    /// There must only ever be one instance of the DMA channel for each timer
+    pub(crate) unsafe fn new() -> Self {
+        DmaChannel { _x: PhantomData }
+    }
+}
+
 pub trait HrTimer {
     type Timer: Instance;
     type Prescaler: HrtimPrescaler;
@@ -147,6 +316,13 @@ pub trait HrTimer {
     /// NOTE: The least significant bits may not be significant depending on prescaler
     fn get_counter_value(&self) -> u16;
 
+    /// Get the counting mode of the timer, the master timer is always [`HrCountingDirection::Up`]
+    ///
+    /// NOTE: The current counting direction in [`HrCountingDirection::UpDown`] mode is not
+    /// available as a timer status bit. It is only reported together with captured values,
+    /// see [`crate::capture::HrCapture::get_last`] and [`crate::capture::HrCapt::trigger_now`].
+    fn counting_mode(&self) -> HrCountingDirection;
+
     /// Start timer
     fn start(&mut self, _hr_control: &mut HrPwmCtrl);
 
@@ -158,6 +334,33 @@ pub trait HrTimer {
 
     fn clear_repetition_interrupt(&mut self);
 
+    /// Start listening to the specified interrupt
+    ///
+    /// NOTE: With the `async` feature, an interrupt that was enabled by a waiting future is
+    /// disabled again by the interrupt handler once it fires. Call this after the future has
+    /// completed to keep it enabled.
+    ///
+    /// Panic if the interrupt is not available for this timer, see [`HrTimerInterrupt::is_available`]
+    fn listen(&mut self, interrupt: HrTimerInterrupt);
+
+    /// Stop listening to the specified interrupt
+    ///
+    /// Panic if the interrupt is not available for this timer, see [`HrTimerInterrupt::is_available`]
+    fn unlisten(&mut self, interrupt: HrTimerInterrupt);
+
+    /// Returns true if the interrupt flag is set
+    ///
+    /// NOTE: The flag is set regardless of if the interrupt is listened to or not
+    fn is_pending(&self, interrupt: HrTimerInterrupt) -> bool;
+
+    /// Clear the interrupt flag
+    fn clear(&mut self, interrupt: HrTimerInterrupt);
+
+    /// Get all the interrupt flags that are set, read at the same time
+    ///
+    /// NOTE: The flags are set regardless of if the interrupts are listened to or not
+    fn pending_set(&self) -> HrTimerInterruptSet;
+
     /// Make a handle to this timers reset/roll-over event to use as adc trigger
     fn as_reset_adc_trigger(&self) -> super::adc_trigger::TimerReset<Self::Timer>;
 
@@ -207,6 +410,59 @@ pub struct TimerSplitCapture<T, PSCL, CH1, CH2, DacRst: DacResetTrigger> {
     pub ch2: HrCapt<T, PSCL, CH2, capture::NoDma>,
 }
 
+#[cfg(feature = "hrtim_v2")]
+fn is_up_down(timer: TimX) -> bool {
+    // SAFETY: Read only access
+    let cr2 = unsafe {
+        match timer {
+            TimX::A => TimExt::cr2(&*HRTIM_TIMA::ptr()),
+            TimX::B => TimExt::cr2(&*HRTIM_TIMB::ptr()),
+            TimX::C => TimExt::cr2(&*HRTIM_TIMC::ptr()),
+            TimX::D => TimExt::cr2(&*HRTIM_TIMD::ptr()),
+            TimX::E => TimExt::cr2(&*HRTIM_TIME::ptr()),
+            TimX::F => TimExt::cr2(&*HRTIM_TIMF::ptr()),
+        }
+    };
+
+    cr2.read().udm().bit()
+}
+
+/// Period register of the timer, for code that only knows the timer at runtime
+///
+/// # Safety
+/// The caller has to own the period of the timer
+pub(crate) unsafe fn timx_perr(timer: TimX) -> &'static crate::pac::hrtim_master::PERR {
+    unsafe {
+        match timer {
+            TimX::A => MasterExt::perr(&*HRTIM_TIMA::ptr()),
+            TimX::B => MasterExt::perr(&*HRTIM_TIMB::ptr()),
+            TimX::C => MasterExt::perr(&*HRTIM_TIMC::ptr()),
+            TimX::D => MasterExt::perr(&*HRTIM_TIMD::ptr()),
+            TimX::E => MasterExt::perr(&*HRTIM_TIME::ptr()),
+            #[cfg(feature = "hrtim_v2")]
+            TimX::F => MasterExt::perr(&*HRTIM_TIMF::ptr()),
+        }
+    }
+}
+
+/// Compare register of the timer, for code that only knows the timer at runtime
+///
+/// # Safety
+/// The caller has to own the compare register
+pub(crate) unsafe fn timx_cmpr(timer: TimX, cmp: Cmp) -> &'static crate::pac::hrtim_master::CMP1R {
+    unsafe {
+        match timer {
+            TimX::A => MasterExt::cmpr(&*HRTIM_TIMA::ptr(), cmp),
+            TimX::B => MasterExt::cmpr(&*HRTIM_TIMB::ptr(), cmp),
+            TimX::C => MasterExt::cmpr(&*HRTIM_TIMC::ptr(), cmp),
+            TimX::D => MasterExt::cmpr(&*HRTIM_TIMD::ptr(), cmp),
+            TimX::E => MasterExt::cmpr(&*HRTIM_TIME::ptr(), cmp),
+            #[cfg(feature = "hrtim_v2")]
+            TimX::F => MasterExt::cmpr(&*HRTIM_TIMF::ptr(), cmp),
+        }
+    }
+}
+
 /// Trait for unsplit slave timer which still contains its capture modules
 pub trait HrSlaveTimerCpt: HrSlaveTimer {
     type CaptureCh1: HrCapture;
@@ -242,6 +498,17 @@ impl<TIM: Instance, PSCL: HrtimPrescaler, CPT1, CPT2, DacRst: DacResetTrigger> H
         tim.cntr().read().cnt().bits()
     }
 
+    fn counting_mode(&self) -> HrCountingDirection {
+        #[cfg(feature = "hrtim_v2")]
+        if let Timer::Tim(t) = TIM::TIMX {
+            if is_up_down(t) {
+                return HrCountingDirection::UpDown;
+            }
+        }
+
+        HrCountingDirection::Up
+    }
+
     /// Start timer
     fn start(&mut self, _hr_control: &mut HrPwmCtrl) {
         // Start timer
@@ -292,6 +559,43 @@ impl<TIM: Instance, PSCL: HrtimPrescaler, CPT1, CPT2, DacRst: DacResetTrigger> H
         tim.icr().write(|w| w.repc().clear());
     }
 
+    fn listen(&mut self, interrupt: HrTimerInterrupt) {
+        assert!(interrupt.is_available(TIM::TIMX));
+
+        modify_dier::<TIM>(|bits| bits | interrupt.bits());
+    }
+
+    fn unlisten(&mut self, interrupt: HrTimerInterrupt) {
+        assert!(interrupt.is_available(TIM::TIMX));
+
+        modify_dier::<TIM>(|bits| bits & !interrupt.bits());
+    }
+
+    fn is_pending(&self, interrupt: HrTimerInterrupt) -> bool {
+        let tim = unsafe { &*TIM::ptr() };
+
+        // No need for exclusive access since this is a read only register
+        interrupt.is_available(TIM::TIMX) && tim.isr().read().bits() & interrupt.bits() != 0
+    }
+
+    fn clear(&mut self, interrupt: HrTimerInterrupt) {
+        assert!(interrupt.is_available(TIM::TIMX));
+        let tim = unsafe { &*TIM::ptr() };
+
+        // No need for exclusive access since this is a write only register
+        unsafe {
+            tim.icr().write(|w| w.bits(interrupt.bits()));
+        }
+    }
+
+    fn pending_set(&self) -> HrTimerInterruptSet {
+        let tim = unsafe { &*TIM::ptr() };
+
+        // No need for exclusive access since this is a read only register
+        let bits = tim.isr().read().bits();
+        HrTimerInterruptSet(bits & HrTimerInterrupt::all_bits(TIM::TIMX))
+    }
+
     /// Disable register updates
     ///
     /// Calling this function temporarily disables the transfer from preload to active registers,
@@ -335,9 +639,9 @@ where
     }
 
     pub fn enable_repetition_interrupt(&mut self, enable: bool) {
-        let tim = unsafe { &*TIM::ptr() };
+        let bit = HrTimerInterrupt::Repetition.bits();
 
-        tim.dier().modify(|_r, w| w.repie().bit(enable));
+        modify_dier::<TIM>(|bits| if enable { bits | bit } else { bits & !bit });
     }
 }
 
@@ -0,0 +1,261 @@
+use crate::compare_register::{CmpExt, HrCompareRegister, HrCr};
+use crate::control::HrPwmCtrl;
+use crate::ext::{Chan, Cmp};
+use crate::output::{HrOut, HrOutputSet};
+use crate::pac::HRTIM_MASTER;
+use crate::timer::{timx_cmpr, timx_perr, ChExt, HrTim, InstanceX, TimX};
+use crate::{DacResetTrigger, DacStepTrigger, HrtimPrescaler};
+
+/// Type erased slave timer, see [`TimerGroup`]
+///
+/// Created from any [`HrTim`] of timer A..F using `From`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug)]
+pub struct AnyHrTimer {
+    timer: TimX,
+}
+
+impl<TIM: InstanceX, PSCL, CPT1, CPT2, DacRst: DacResetTrigger>
+    From<HrTim<TIM, PSCL, CPT1, CPT2, DacRst>> for AnyHrTimer
+{
+    fn from(_timer: HrTim<TIM, PSCL, CPT1, CPT2, DacRst>) -> Self {
+        Self { timer: TIM::T_X }
+    }
+}
+
+impl AnyHrTimer {
+    pub fn timer(&self) -> TimX {
+        self.timer
+    }
+
+    pub fn get_period(&self) -> u16 {
+        // SAFETY: Read only access
+        unsafe { timx_perr(self.timer) }.read().per().bits()
+    }
+
+    /// Set period of timer
+    pub fn set_period(&mut self, period: u16) {
+        // SAFETY: We own the timer
+        unsafe { timx_perr(self.timer) }.write(|w| unsafe { w.per().bits(period) });
+    }
+
+    /// Start timer
+    pub fn start(&mut self, _hr_control: &mut HrPwmCtrl) {
+        let master = unsafe { &*HRTIM_MASTER::ptr() };
+        master
+            .cr()
+            .modify(|_r, w| w.tcen(self.timer as _).set_bit());
+    }
+
+    /// Stop timer
+    pub fn stop(&mut self, _hr_control: &mut HrPwmCtrl) {
+        let master = unsafe { &*HRTIM_MASTER::ptr() };
+        master
+            .cr()
+            .modify(|_r, w| w.tcen(self.timer as _).clear_bit());
+    }
+}
+
+/// Type erased compare register of a slave timer, see [`TimerGroup`]
+///
+/// Created from any [`HrCr`] of timer A..F using `From`, the limits of its
+/// prescaler are kept.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug)]
+pub struct AnyHrCr {
+    timer: TimX,
+    cmp: Cmp,
+    min_cr: u16,
+    max_cr: u16,
+}
+
+impl<TIM: InstanceX, PSCL: HrtimPrescaler, CMP: CmpExt, DacStp: DacStepTrigger>
+    From<HrCr<TIM, PSCL, CMP, DacStp>> for AnyHrCr
+{
+    fn from(_cr: HrCr<TIM, PSCL, CMP, DacStp>) -> Self {
+        Self {
+            timer: TIM::T_X,
+            cmp: CMP::CMP,
+            min_cr: PSCL::MIN_CR,
+            max_cr: PSCL::MAX_CR,
+        }
+    }
+}
+
+impl AnyHrCr {
+    pub fn timer(&self) -> TimX {
+        self.timer
+    }
+
+    pub fn cmp(&self) -> Cmp {
+        self.cmp
+    }
+}
+
+impl HrCompareRegister for AnyHrCr {
+    fn get_duty(&self) -> u16 {
+        // SAFETY: Read only access
+        unsafe { timx_cmpr(self.timer, self.cmp) }
+            .read()
+            .cmp()
+            .bits()
+    }
+
+    fn set_duty(&mut self, duty: u16) {
+        // SAFETY: We own the compare register
+        unsafe { timx_cmpr(self.timer, self.cmp) }.write(|w| unsafe { w.cmp().bits(duty) });
+    }
+
+    fn min_duty(&self) -> u16 {
+        self.min_cr
+    }
+
+    fn max_duty(&self) -> u16 {
+        self.max_cr
+    }
+
+    fn allows_zero(&self) -> bool {
+        matches!(self.cmp, Cmp::Cmp1 | Cmp::Cmp3)
+    }
+}
+
+/// Type erased output of a slave timer
+///
+/// Created from any [`HrOut`] of timer A..F using `From`. Use [`HrOutputSet`] to
+/// enable/disable a whole array of outputs at the same time.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug)]
+pub struct AnyHrOut {
+    timer: TimX,
+    ch: Chan,
+}
+
+impl<TIM: InstanceX, PSCL, CH: ChExt, R: DacResetTrigger, S: DacStepTrigger>
+    From<HrOut<TIM, PSCL, CH, R, S>> for AnyHrOut
+{
+    fn from(_out: HrOut<TIM, PSCL, CH, R, S>) -> Self {
+        Self {
+            timer: TIM::T_X,
+            ch: CH::CH,
+        }
+    }
+}
+
+impl AnyHrOut {
+    pub fn timer(&self) -> TimX {
+        self.timer
+    }
+
+    pub fn channel(&self) -> Chan {
+        self.ch
+    }
+
+    pub fn enable(&mut self) {
+        self.enable_all();
+    }
+
+    pub fn disable(&mut self) {
+        self.disable_all();
+    }
+}
+
+impl HrOutputSet for AnyHrOut {
+    fn output_bits(&self) -> u32 {
+        1 << (self.timer as u32 * 2 + self.ch as u32) // TA1OEN is at bit 0, TA2OEN at bit 1, TB1OEN at bit 2 etc
+    }
+}
+
+/// A group of N slave timers with one compare register each, indexable at runtime
+///
+/// ```
+/// let mut group = TimerGroup::new(
+///     [timer_a.into(), timer_b.into(), timer_c.into()],
+///     [cr1_a.into(), cr1_b.into(), cr1_c.into()],
+/// );
+///
+/// group.set_period(20_000);
+/// for (phase, duty) in duties.iter().enumerate() {
+///     group.set_duty(phase, *duty);
+/// }
+/// group.start(&mut hr_control.control);
+/// ```
+pub struct TimerGroup<const N: usize> {
+    timers: [AnyHrTimer; N],
+    crs: [AnyHrCr; N],
+}
+
+impl<const N: usize> TimerGroup<N> {
+    /// Panic if `crs[i]` does not belong to `timers[i]`
+    pub fn new(timers: [AnyHrTimer; N], crs: [AnyHrCr; N]) -> Self {
+        for (timer, cr) in timers.iter().zip(crs.iter()) {
+            assert_eq!(timer.timer, cr.timer);
+        }
+
+        Self { timers, crs }
+    }
+
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Set the compare value of timer `i`, saturated to the range allowed by the prescaler
+    ///
+    /// Returns the value actually written
+    ///
+    /// Panic if `i >= N`
+    pub fn set_duty(&mut self, i: usize, duty: u16) -> u16 {
+        self.crs[i].set_duty_clamped(duty)
+    }
+
+    /// Panic if `i >= N`
+    pub fn get_duty(&self, i: usize) -> u16 {
+        self.crs[i].get_duty()
+    }
+
+    /// Set the period of all timers in the group
+    pub fn set_period(&mut self, period: u16) {
+        for timer in &mut self.timers {
+            timer.set_period(period);
+        }
+    }
+
+    /// Start all timers in the group at the exact same time
+    pub fn start(&mut self, _hr_control: &mut HrPwmCtrl) {
+        let master = unsafe { &*HRTIM_MASTER::ptr() };
+        master.cr().modify(|_r, w| {
+            for timer in &self.timers {
+                w.tcen(timer.timer as _).set_bit();
+            }
+            w
+        });
+    }
+
+    /// Stop all timers in the group at the exact same time
+    pub fn stop(&mut self, _hr_control: &mut HrPwmCtrl) {
+        let master = unsafe { &*HRTIM_MASTER::ptr() };
+        master.cr().modify(|_r, w| {
+            for timer in &self.timers {
+                w.tcen(timer.timer as _).clear_bit();
+            }
+            w
+        });
+    }
+
+    /// Panic if `i >= N`
+    pub fn timer(&mut self, i: usize) -> &mut AnyHrTimer {
+        &mut self.timers[i]
+    }
+
+    /// Panic if `i >= N`
+    pub fn cr(&mut self, i: usize) -> &mut AnyHrCr {
+        &mut self.crs[i]
+    }
+
+    pub fn free(self) -> ([AnyHrTimer; N], [AnyHrCr; N]) {
+        (self.timers, self.crs)
+    }
+}